@@ -0,0 +1,500 @@
+use std::path::Path;
+use std::path::PathBuf;
+
+use ray_tracing::utils::color::Transfer;
+use ray_tracing::utils::render::RenderSettings;
+use ray_tracing::utils::scene::BUILTIN_SCENES;
+use ray_tracing::utils::tonemap::Operator;
+use ray_tracing::utils::tonemap::ToneMapper;
+
+pub const USAGE: &str = "\
+Usage: ray_tracing [OPTIONS]
+
+Options:
+  -o, --output <PATH>      File to write, `-` for stdout [default: image.png]
+  -f, --format <FORMAT>    ppm, p3, png, hdr or pfm [default: from the output
+                           extension, png otherwise]
+  -s, --scene <SCENE>      spheres, lights, random or the path to a scene
+                           file, `.json` files are read as JSON
+                           [default: spheres]
+      --export <PATH>      Also write the scene and settings used as JSON
+  -w, --width <PIXELS>     Image width [default: 400, or from the height]
+  -h, --height <PIXELS>    Image height [default: width at 16:9]
+      --samples <N>        Samples per pixel [default: 100]
+      --max-depth <N>      Maximum number of bounces [default: 50]
+      --seed <N>           Seed of the random streams [default: 0]
+  -j, --threads <N>        Render threads [default: one per core]
+      --tonemap <OP>       clip, reinhard, reinhard:<WHITE> or aces
+                           [default: clip]
+      --exposure <STOPS>   Exposure adjustment [default: 0]
+      --transfer <CURVE>   srgb, linear or a gamma such as 2.2
+                           [default: srgb]
+      --help               Print this message
+
+Size, sample, depth and seed options override the scene file's settings.
+
+hdr and pfm images hold linear radiance, tone mapping and the transfer curve
+only apply to 8 bit formats.";
+
+/// Every option that takes a value.
+const OPTIONS: [&str; 19] = [
+    "-o",
+    "--output",
+    "-f",
+    "--format",
+    "-s",
+    "--scene",
+    "-w",
+    "--width",
+    "-h",
+    "--height",
+    "--samples",
+    "--max-depth",
+    "--seed",
+    "-j",
+    "--threads",
+    "--tonemap",
+    "--exposure",
+    "--transfer",
+    "--export",
+];
+
+/// File format of the rendered image.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Format {
+    /// Binary `P6`.
+    Ppm,
+    /// Plain text `P3`.
+    P3,
+    Png,
+    /// Radiance RGBE.
+    Hdr,
+    Pfm,
+}
+
+impl Format {
+    fn parse(name: &str) -> Option<Self> {
+        match name.to_ascii_lowercase().as_str() {
+            "ppm" | "p6" => Some(Format::Ppm),
+            "p3" => Some(Format::P3),
+            "png" => Some(Format::Png),
+            "hdr" => Some(Format::Hdr),
+            "pfm" => Some(Format::Pfm),
+            _ => None,
+        }
+    }
+
+    fn from_path(path: &Path) -> Option<Self> {
+        Self::parse(path.extension()?.to_str()?)
+    }
+}
+
+/// Where the scene comes from.
+#[derive(Debug, PartialEq)]
+pub enum SceneSource {
+    /// One of `BUILTIN_SCENES`.
+    Builtin(String),
+    /// A scene description file.
+    File(PathBuf),
+}
+
+/// Settings for a single render.
+#[derive(Debug, PartialEq)]
+pub struct Options {
+    /// Settings given on the command line, they take precedence over the
+    /// scene file.
+    pub settings: RenderSettings,
+    /// `None` uses one thread per core.
+    pub threads: Option<usize>,
+    /// `None` writes to stdout.
+    pub output: Option<PathBuf>,
+    pub format: Format,
+    pub scene: SceneSource,
+    /// Where to write the scene as JSON, if anywhere.
+    pub export: Option<PathBuf>,
+    pub tone_mapper: ToneMapper,
+    pub transfer: Transfer,
+}
+
+#[derive(Debug, PartialEq)]
+pub enum Command {
+    Render(Box<Options>),
+    Help,
+}
+
+/// Error returned when the command line can't be parsed.
+#[derive(Debug, PartialEq)]
+pub enum CliError {
+    UnknownOption(String),
+    MissingValue(String),
+    InvalidValue { option: String, value: String },
+}
+
+impl std::fmt::Display for CliError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            CliError::UnknownOption(option) => {
+                write!(f, "unknown option `{}`", option)
+            }
+            CliError::MissingValue(option) => {
+                write!(f, "`{}` needs a value", option)
+            }
+            CliError::InvalidValue { option, value } => {
+                write!(
+                    f,
+                    "invalid value `{}` for `{}`",
+                    value, option
+                )
+            }
+        }
+    }
+}
+
+impl std::error::Error for CliError {}
+
+/// Parses the arguments following the program name.
+///
+/// Values can be given either as `--option value` or `--option=value`.
+pub fn parse<I>(args: I) -> Result<Command, CliError>
+where
+    I: IntoIterator<Item = String>,
+{
+    let mut settings = RenderSettings::default();
+    let mut threads = None;
+    let mut output = Some(PathBuf::from("image.png"));
+    let mut format = None;
+    let mut scene = SceneSource::Builtin(String::from("spheres"));
+    let mut export = None;
+    let mut tone_mapper = ToneMapper::default();
+    let mut transfer = Transfer::Srgb;
+
+    let mut args = args.into_iter();
+    while let Some(arg) = args.next() {
+        let (option, inline) = match arg.split_once('=') {
+            Some((option, value)) if option.starts_with("--") => (
+                option.to_string(),
+                Some(value.to_string()),
+            ),
+            _ => (arg, None),
+        };
+
+        if option == "--help" {
+            return Ok(Command::Help);
+        }
+
+        if !OPTIONS.contains(&option.as_str()) {
+            return Err(CliError::UnknownOption(option));
+        }
+        let value = match inline.or_else(|| args.next()) {
+            Some(value) => value,
+            None => return Err(CliError::MissingValue(option)),
+        };
+        let invalid = || CliError::InvalidValue {
+            option: option.clone(),
+            value: value.clone(),
+        };
+
+        match option.as_str() {
+            "-o" | "--output" => {
+                output = match value.as_str() {
+                    "-" => None,
+                    path => Some(PathBuf::from(path)),
+                }
+            }
+            "-f" | "--format" => {
+                format = Some(Format::parse(&value).ok_or_else(invalid)?)
+            }
+            "-s" | "--scene" => {
+                scene = if BUILTIN_SCENES.contains(&value.as_str()) {
+                    SceneSource::Builtin(value.clone())
+                } else {
+                    SceneSource::File(PathBuf::from(&value))
+                }
+            }
+            "-w" | "--width" => {
+                settings.width = Some(positive(&value).ok_or_else(invalid)?)
+            }
+            "-h" | "--height" => {
+                settings.height = Some(positive(&value).ok_or_else(invalid)?)
+            }
+            "--samples" => {
+                settings.samples_per_pixel =
+                    Some(positive(&value).ok_or_else(invalid)?)
+            }
+            "--max-depth" => {
+                settings.max_depth =
+                    Some(positive(&value).ok_or_else(invalid)?)
+            }
+            "--seed" => {
+                settings.seed = Some(value.parse().map_err(|_| invalid())?)
+            }
+            "-j" | "--threads" => {
+                threads = Some(positive(&value).ok_or_else(invalid)?)
+            }
+            "--export" => export = Some(PathBuf::from(&value)),
+            "--tonemap" => {
+                tone_mapper.operator = operator(&value).ok_or_else(invalid)?
+            }
+            "--exposure" => {
+                tone_mapper.exposure = finite(&value).ok_or_else(invalid)?
+            }
+            "--transfer" => {
+                transfer = match value.as_str() {
+                    "srgb" => Transfer::Srgb,
+                    "linear" => Transfer::Linear,
+                    gamma => Transfer::Gamma(
+                        finite(gamma)
+                            .filter(|gamma| *gamma > 0.0)
+                            .ok_or_else(invalid)?,
+                    ),
+                }
+            }
+            _ => unreachable!("every option is listed in `OPTIONS`"),
+        }
+    }
+
+    let format = format
+        .or_else(|| output.as_deref().and_then(Format::from_path))
+        .unwrap_or(Format::Png);
+
+    Ok(Command::Render(Box::new(
+        Options {
+            settings,
+            threads,
+            output,
+            format,
+            scene,
+            export,
+            tone_mapper,
+            transfer,
+        },
+    )))
+}
+
+/// Parses an integer greater than zero.
+fn positive<T>(value: &str) -> Option<T>
+where
+    T: std::str::FromStr + Default + PartialOrd,
+{
+    value.parse().ok().filter(|parsed| *parsed > T::default())
+}
+
+fn finite(value: &str) -> Option<f64> {
+    value.parse().ok().filter(|parsed: &f64| parsed.is_finite())
+}
+
+fn operator(value: &str) -> Option<Operator> {
+    match value {
+        "clip" => Some(Operator::Clip),
+        "reinhard" => Some(Operator::Reinhard),
+        "aces" => Some(Operator::Aces),
+        _ => {
+            let white = value.strip_prefix("reinhard:")?;
+            let white = finite(white).filter(|white| *white > 0.0)?;
+            Some(Operator::ExtendedReinhard { white })
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn render(args: &[&str]) -> Options {
+        match parse(args.iter().map(|arg| arg.to_string())) {
+            Ok(Command::Render(options)) => *options,
+            other => panic!(
+                "expected render options, got {:?}",
+                other
+            ),
+        }
+    }
+
+    fn error(args: &[&str]) -> CliError {
+        parse(args.iter().map(|arg| arg.to_string())).unwrap_err()
+    }
+
+    #[test]
+    fn defaults() {
+        let options = render(&[]);
+
+        assert_eq!(
+            options.settings,
+            RenderSettings::default()
+        );
+        assert_eq!(options.threads, None);
+        assert_eq!(
+            options.output,
+            Some(PathBuf::from("image.png"))
+        );
+        assert_eq!(options.format, Format::Png);
+        assert_eq!(options.export, None);
+        assert_eq!(
+            options.scene,
+            SceneSource::Builtin("spheres".to_string())
+        );
+        assert_eq!(
+            options.tone_mapper,
+            ToneMapper::default()
+        );
+        assert_eq!(options.transfer, Transfer::Srgb);
+    }
+
+    #[test]
+    fn every_option() {
+        let options = render(&[
+            "-w",
+            "64",
+            "--height=48",
+            "--samples",
+            "8",
+            "--max-depth",
+            "4",
+            "--seed",
+            "7",
+            "-j",
+            "2",
+            "-o",
+            "out.bin",
+            "--format",
+            "P3",
+            "--scene",
+            "lights",
+            "--export",
+            "scene.json",
+            "--tonemap",
+            "reinhard:4",
+            "--exposure=-1.5",
+            "--transfer",
+            "2.2",
+        ]);
+
+        assert_eq!(
+            options.settings,
+            RenderSettings {
+                width: Some(64),
+                height: Some(48),
+                samples_per_pixel: Some(8),
+                max_depth: Some(4),
+                seed: Some(7),
+            }
+        );
+        assert_eq!(options.threads, Some(2));
+        assert_eq!(
+            options.output,
+            Some(PathBuf::from("out.bin"))
+        );
+        assert_eq!(options.format, Format::P3);
+        assert_eq!(
+            options.export,
+            Some(PathBuf::from("scene.json"))
+        );
+        assert_eq!(
+            options.scene,
+            SceneSource::Builtin("lights".to_string())
+        );
+        assert_eq!(
+            options.tone_mapper.operator,
+            Operator::ExtendedReinhard { white: 4.0 }
+        );
+        assert_eq!(options.tone_mapper.exposure, -1.5);
+        assert_eq!(
+            options.transfer,
+            Transfer::Gamma(2.2)
+        );
+    }
+
+    #[test]
+    fn scene_file() {
+        assert_eq!(
+            render(&["--scene", "scenes/spheres.scene"]).scene,
+            SceneSource::File(PathBuf::from(
+                "scenes/spheres.scene"
+            ))
+        );
+    }
+
+    #[test]
+    fn format_from_extension() {
+        assert_eq!(
+            render(&["-o", "a.hdr"]).format,
+            Format::Hdr
+        );
+        assert_eq!(
+            render(&["-o", "a.PFM"]).format,
+            Format::Pfm
+        );
+        assert_eq!(
+            render(&["-o", "a.ppm"]).format,
+            Format::Ppm
+        );
+        assert_eq!(
+            render(&["-o", "a"]).format,
+            Format::Png
+        );
+    }
+
+    #[test]
+    fn stdout() {
+        let options = render(&["-o", "-", "-f", "ppm"]);
+        assert_eq!(options.output, None);
+        assert_eq!(options.format, Format::Ppm);
+        assert_eq!(
+            render(&["-o", "-"]).format,
+            Format::Png
+        );
+    }
+
+    #[test]
+    fn help() {
+        assert_eq!(
+            parse(vec![
+                "-w".to_string(),
+                "2".to_string(),
+                "--help".to_string()
+            ]),
+            Ok(Command::Help)
+        );
+    }
+
+    #[test]
+    fn errors() {
+        assert_eq!(
+            error(&["--bogus", "1"]),
+            CliError::UnknownOption("--bogus".to_string())
+        );
+        assert_eq!(
+            error(&["stray"]),
+            CliError::UnknownOption("stray".to_string())
+        );
+        assert_eq!(
+            error(&["--bogus"]),
+            CliError::UnknownOption("--bogus".to_string())
+        );
+        assert_eq!(
+            error(&["--samples"]),
+            CliError::MissingValue("--samples".to_string())
+        );
+        assert_eq!(
+            error(&["--width", "0"]),
+            CliError::InvalidValue {
+                option: "--width".to_string(),
+                value: "0".to_string(),
+            }
+        );
+
+        for args in [
+            ["--format", "gif"],
+            ["--samples", "-1"],
+            ["--seed", "x"],
+            ["--tonemap", "reinhard:0"],
+            ["--exposure", "inf"],
+            ["--transfer", "0"],
+        ] {
+            assert!(matches!(
+                error(&args),
+                CliError::InvalidValue { .. }
+            ));
+        }
+    }
+}
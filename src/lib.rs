@@ -1,12 +1,29 @@
 pub mod utils {
+    pub mod bvh;
     pub mod camera;
+    pub mod color;
+    pub mod hdr;
     pub mod hittable;
+    pub mod image;
+    pub mod json;
+    pub mod material;
+    pub mod mesh;
+    pub mod obj;
+    pub mod png;
     pub mod ppm;
+    pub mod render;
+    pub mod scene;
+    pub mod scene_file;
+    pub mod scene_json;
     pub mod sphere;
+    pub mod tonemap;
+    pub mod triangle;
     pub mod world;
 }
 
 pub mod math {
+    pub mod aabb;
+    pub mod random;
     pub mod ray;
     pub mod vector3;
 }
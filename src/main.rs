@@ -1,100 +1,117 @@
 use indicatif::ProgressBar;
 use indicatif::ProgressStyle;
 
-use math::ray::Ray;
-use math::vector3::Vector3;
-use ray_tracing::math;
 use ray_tracing::utils;
-use utils::camera::Camera;
-use utils::hittable::*;
-use utils::ppm::RGBTriplet;
-use utils::ppm::PPM;
-use utils::sphere::Sphere;
-use utils::world::World;
-
-fn ray_color(mut ray: Ray, world: &World) -> RGBTriplet {
-    if let Some(hit) = world.hit(&ray, 0.0..=f64::MAX) {
-        return RGBTriplet::from_vector3(
-            (hit.normal + Vector3::new(1.0, 1.0, 1.0)).color() * 0.5,
-        );
-    }
+use std::fs::File;
+use std::io::BufWriter;
+use std::io::Write;
+use utils::bvh::SplitMethod;
+use utils::png;
+use utils::png::Compression;
+use utils::ppm::PpmFormat;
+use utils::render::RenderSettings;
+use utils::scene::Scene;
 
-    let unit_direction = ray.direction.unit();
-    let t = 0.5 * (unit_direction.y + 1.0);
-    let v = Vector3::lerp(
-        Vector3::new(1.0, 1.0, 1.0),
-        Vector3::new(0.5, 0.7, 1.0),
-        t,
-    )
-    .color();
+mod cli;
 
-    RGBTriplet::from_vector3(v)
-}
+use cli::Command;
+use cli::Format;
+use cli::Options;
+use cli::SceneSource;
 
 fn main() {
-    // Image
-    let aspect_ratio = 16.0 / 9.0;
-    let image_width = 400;
-    let image_height = (image_width as f64 / aspect_ratio) as u64;
+    let options = match cli::parse(std::env::args().skip(1)) {
+        Ok(Command::Render(options)) => *options,
+        Ok(Command::Help) => {
+            println!("{}", cli::USAGE);
+            return;
+        }
+        Err(error) => {
+            eprintln!(
+                "error: {}\n\n{}",
+                error,
+                cli::USAGE
+            );
+            std::process::exit(2);
+        }
+    };
+
+    if let Err(error) = render(&options) {
+        eprintln!("error: {}", error);
+        std::process::exit(1);
+    }
+}
+
+fn render(options: &Options) -> Result<(), Box<dyn std::error::Error>> {
+    let mut scene = match &options.scene {
+        SceneSource::Builtin(name) => Scene::builtin(name)
+            .expect("scene names are checked while parsing"),
+        SceneSource::File(path) => Scene::open(path)
+            .map_err(|error| format!("{}: {}", path.display(), error))?,
+    };
+
+    // Settings given on the command line win over the scene's.
+    let settings = options.settings.or(scene.settings);
+    let (width, height) = settings.size();
+    let camera = scene.camera.build(width as f64 / height as f64);
+
+    let mut renderer = settings.renderer();
+    renderer.background = scene.background;
+    if let Some(threads) = options.threads {
+        renderer.threads = threads;
+    }
+
+    // Snapshot the exact scene and settings used for this render.
+    if let Some(path) = &options.export {
+        scene.settings = RenderSettings {
+            width: Some(width),
+            height: Some(height),
+            samples_per_pixel: Some(renderer.samples_per_pixel),
+            max_depth: Some(renderer.max_depth),
+            seed: Some(renderer.seed),
+        };
+        let json = scene
+            .to_json()
+            .map_err(|error| format!("{}: {}", path.display(), error))?;
+        std::fs::write(path, format!("{}\n", json))?;
+    }
+
+    scene.world.build_bvh(SplitMethod::Sah { bins: 16 });
 
     // Set up
-    let bar = ProgressBar::new(image_width * image_height);
-    bar.set_draw_delta((image_width * image_height) / 100);
+    let pixels = width * height;
+    let bar = ProgressBar::new(pixels);
+    bar.set_draw_delta((pixels / 100).max(1));
     bar.set_style(
         ProgressStyle::default_bar().template(
-            "LOADING PPM: {percent}% {bar:40.cyan/blue} {pos:>7}/{len:7}",
+            "RENDERING: {percent}% {bar:40.cyan/blue} {pos:>7}/{len:7}",
         ),
     );
 
-    let mut ppm = PPM::new(image_width, image_height);
-
-    // World
-    let mut world = World::default();
-    world.add(Box::new(Sphere::new(
-        Vector3::new(0.0, 0.0, -1.0),
-        0.5,
-    )));
-    world.add(Box::new(Sphere::new(
-        Vector3::new(0.0, -100.5, -1.0),
-        100.0,
-    )));
-
-    // Camera
-    let camera = Camera::new(
-        2.0,
-        aspect_ratio * 2.0,
-        1.0,
-        Vector3::new(0.0, 0.0, 0.0),
-    );
+    // Open the output before rendering so a bad path fails early.
+    let writer: Box<dyn Write> = match &options.output {
+        Some(path) => Box::new(File::create(path)?),
+        None => Box::new(std::io::stdout()),
+    };
+    let mut writer = BufWriter::new(writer);
 
-    let lower_left_corner = camera.position
-        - camera.horizontal / 2.0
-        - camera.vertical / 2.0
-        - Vector3::new(0.0, 0.0, camera.focal_length);
-
-    for j in (0..image_height).rev() {
-        for i in 0..image_width {
-            bar.inc(1);
-
-            let u = i as f64 / (image_width - 1) as f64;
-            let v = j as f64 / (image_height - 1) as f64;
-            let r = Ray::new(
-                camera.position,
-                lower_left_corner
-                    + u * camera.horizontal
-                    + v * camera.vertical
-                    - camera.position,
-            );
-
-            ppm.set(
-                i,
-                image_height - j - 1,
-                ray_color(r, &world),
-            );
-        }
-    }
+    let image = renderer.render(&scene.world, &camera, &bar);
 
     bar.finish();
 
-    print!("{:?}", ppm);
+    let to_ppm = || options.tone_mapper.apply(&image).to_ppm(options.transfer);
+    match options.format {
+        Format::Ppm => to_ppm().write(&mut writer, PpmFormat::Binary)?,
+        Format::P3 => to_ppm().write(&mut writer, PpmFormat::Ascii)?,
+        Format::Png => png::write(
+            &to_ppm(),
+            &mut writer,
+            Compression::Fixed,
+        )?,
+        Format::Hdr => image.write_hdr(&mut writer)?,
+        Format::Pfm => image.write_pfm(&mut writer)?,
+    }
+
+    writer.flush()?;
+    Ok(())
 }
@@ -0,0 +1,163 @@
+use super::ray::Ray;
+use super::vector3::Vector3;
+use std::ops::RangeInclusive;
+
+/// Axis aligned bounding box.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Aabb {
+    pub min: Vector3,
+    pub max: Vector3,
+}
+
+impl Aabb {
+    pub fn new(min: Vector3, max: Vector3) -> Self {
+        Self { min, max }
+    }
+
+    /// Returns the smallest box containing both boxes.
+    pub fn surrounding(&self, other: &Aabb) -> Self {
+        Self {
+            min: Vector3::new(
+                self.min.x.min(other.min.x),
+                self.min.y.min(other.min.y),
+                self.min.z.min(other.min.z),
+            ),
+            max: Vector3::new(
+                self.max.x.max(other.max.x),
+                self.max.y.max(other.max.y),
+                self.max.z.max(other.max.z),
+            ),
+        }
+    }
+
+    pub fn surface_area(&self) -> f64 {
+        let extent = self.max - self.min;
+        2.0 * (extent.x * extent.y + extent.y * extent.z + extent.z * extent.x)
+    }
+
+    pub fn centroid(&self) -> Vector3 {
+        0.5 * (self.min + self.max)
+    }
+
+    /// Returns the component of `vector` along `axis`, 0 being x, 1 y and
+    /// 2 z.
+    pub fn axis(vector: Vector3, axis: usize) -> f64 {
+        match axis {
+            0 => vector.x,
+            1 => vector.y,
+            _ => vector.z,
+        }
+    }
+
+    /// Returns the axis the box is longest along.
+    pub fn longest_axis(&self) -> usize {
+        let extent = self.max - self.min;
+
+        if extent.x > extent.y && extent.x > extent.z {
+            0
+        } else if extent.y > extent.z {
+            1
+        } else {
+            2
+        }
+    }
+
+    /// Slab test, returns `true` if the ray enters the box inside
+    /// `valid_range`.
+    pub fn hit(&self, ray: &Ray, valid_range: RangeInclusive<f64>) -> bool {
+        let mut t_min = *valid_range.start();
+        let mut t_max = *valid_range.end();
+
+        for axis in 0..3 {
+            let inverse = 1.0 / Self::axis(ray.direction, axis);
+            let origin = Self::axis(ray.origin, axis);
+
+            let mut t0 = (Self::axis(self.min, axis) - origin) * inverse;
+            let mut t1 = (Self::axis(self.max, axis) - origin) * inverse;
+            if inverse < 0.0 {
+                std::mem::swap(&mut t0, &mut t1);
+            }
+
+            t_min = if t0 > t_min { t0 } else { t_min };
+            t_max = if t1 < t_max { t1 } else { t_max };
+            if t_max < t_min {
+                return false;
+            }
+        }
+
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn unit_box() -> Aabb {
+        Aabb::new(
+            Vector3::new(-1.0, -1.0, -1.0),
+            Vector3::new(1.0, 1.0, 1.0),
+        )
+    }
+
+    #[test]
+    fn ray_through_box() {
+        let ray = Ray::new(
+            Vector3::new(0.0, 0.0, 5.0),
+            Vector3::new(0.0, 0.0, -1.0),
+        );
+        assert!(unit_box().hit(&ray, 0.0..=f64::MAX));
+    }
+
+    #[test]
+    fn ray_missing_box() {
+        let ray = Ray::new(
+            Vector3::new(0.0, 2.0, 5.0),
+            Vector3::new(0.0, 0.0, -1.0),
+        );
+        assert!(!unit_box().hit(&ray, 0.0..=f64::MAX));
+    }
+
+    #[test]
+    fn box_outside_range() {
+        let ray = Ray::new(
+            Vector3::new(0.0, 0.0, 5.0),
+            Vector3::new(0.0, 0.0, -1.0),
+        );
+        assert!(!unit_box().hit(&ray, 0.0..=3.0));
+        assert!(!unit_box().hit(&ray, 7.0..=f64::MAX));
+    }
+
+    #[test]
+    fn surrounding_box() {
+        let other = Aabb::new(
+            Vector3::new(0.0, 0.0, 0.0),
+            Vector3::new(3.0, 2.0, 1.0),
+        );
+        assert_eq!(
+            unit_box().surrounding(&other),
+            Aabb::new(
+                Vector3::new(-1.0, -1.0, -1.0),
+                Vector3::new(3.0, 2.0, 1.0),
+            )
+        );
+    }
+
+    #[test]
+    fn surface_area() {
+        let b = Aabb::new(
+            Vector3::new(0.0, 0.0, 0.0),
+            Vector3::new(1.0, 2.0, 3.0),
+        );
+        assert_eq!(b.surface_area(), 22.0);
+    }
+
+    #[test]
+    fn longest_axis() {
+        let b = Aabb::new(
+            Vector3::new(0.0, 0.0, 0.0),
+            Vector3::new(1.0, 3.0, 2.0),
+        );
+        assert_eq!(b.longest_axis(), 1);
+    }
+}
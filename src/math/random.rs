@@ -0,0 +1,179 @@
+use super::vector3::Vector3;
+
+/// Small SplitMix64 pseudo random number generator.
+///
+/// The same seed always produces the same sequence, on every platform.
+#[derive(Clone)]
+pub struct Rng {
+    state: u64,
+}
+
+impl Rng {
+    pub fn new(seed: u64) -> Self {
+        Self { state: seed }
+    }
+
+    /// Derives an independent generator for `stream`, such as a pixel or
+    /// thread index, from a base `seed`.
+    pub fn from_stream(seed: u64, stream: u64) -> Self {
+        let mut mixer =
+            Self::new(seed ^ stream.wrapping_mul(0xD1B5_4A32_D192_ED03));
+        Self::new(mixer.next_u64() ^ stream)
+    }
+
+    /// Splits off a new generator, advancing this one.
+    pub fn split(&mut self) -> Self {
+        Self::new(self.next_u64())
+    }
+
+    /// Returns the next 64 random bits.
+    pub fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    /// Returns a random number in `[0, 1)`.
+    pub fn next_f64(&mut self) -> f64 {
+        (self.next_u64() >> 11) as f64 / (1u64 << 53) as f64
+    }
+
+    /// Returns a random number in `[min, max)`.
+    pub fn range(&mut self, min: f64, max: f64) -> f64 {
+        min + (max - min) * self.next_f64()
+    }
+
+    /// Returns a random point inside the unit sphere.
+    pub fn in_unit_sphere(&mut self) -> Vector3 {
+        loop {
+            let p = Vector3::new(
+                self.range(-1.0, 1.0),
+                self.range(-1.0, 1.0),
+                self.range(-1.0, 1.0),
+            );
+
+            if p.dot(p) < 1.0 {
+                return p;
+            }
+        }
+    }
+
+    /// Returns a random point inside the unit disk on the xy plane.
+    pub fn in_unit_disk(&mut self) -> Vector3 {
+        loop {
+            let p = Vector3::new(
+                self.range(-1.0, 1.0),
+                self.range(-1.0, 1.0),
+                0.0,
+            );
+
+            if p.dot(p) < 1.0 {
+                return p;
+            }
+        }
+    }
+
+    /// Returns a random point on the surface of the unit sphere.
+    pub fn unit_vector(&mut self) -> Vector3 {
+        self.in_unit_sphere().unit()
+    }
+}
+
+impl Default for Rng {
+    fn default() -> Self {
+        Self::new(0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn same_seed_same_sequence() {
+        let mut a = Rng::new(42);
+        let mut b = Rng::new(42);
+
+        for _ in 0..100 {
+            assert_eq!(a.next_u64(), b.next_u64());
+        }
+    }
+
+    #[test]
+    fn known_sequence() {
+        let mut rng = Rng::new(1234567);
+
+        assert_eq!(rng.next_u64(), 6457827717110365317);
+        assert_eq!(rng.next_u64(), 3203168211198807973);
+    }
+
+    #[test]
+    fn streams_are_reproducible() {
+        let mut a = Rng::from_stream(7, 3);
+        let mut b = Rng::from_stream(7, 3);
+
+        for _ in 0..100 {
+            assert_eq!(a.next_u64(), b.next_u64());
+        }
+    }
+
+    #[test]
+    fn streams_differ() {
+        let first: Vec<u64> = (0..64)
+            .map(|stream| Rng::from_stream(7, stream).next_u64())
+            .collect();
+
+        for (i, a) in first.iter().enumerate() {
+            for b in first.iter().skip(i + 1) {
+                assert_ne!(a, b);
+            }
+        }
+
+        assert_ne!(
+            Rng::from_stream(7, 0).next_u64(),
+            Rng::from_stream(8, 0).next_u64()
+        );
+    }
+
+    #[test]
+    fn split_differs_from_parent() {
+        let mut parent = Rng::new(42);
+        let mut child = parent.split();
+
+        assert_ne!(parent.next_u64(), child.next_u64());
+    }
+
+    #[test]
+    fn next_f64_in_unit_interval() {
+        let mut rng = Rng::default();
+
+        for _ in 0..1000 {
+            let x = rng.next_f64();
+            assert!((0.0..1.0).contains(&x));
+        }
+    }
+
+    #[test]
+    fn in_unit_sphere_is_inside() {
+        let mut rng = Rng::default();
+
+        for _ in 0..1000 {
+            let p = rng.in_unit_sphere();
+            assert!(p.magnitude() < 1.0);
+        }
+    }
+
+    #[test]
+    fn in_unit_disk_is_flat() {
+        let mut rng = Rng::default();
+
+        for _ in 0..1000 {
+            let p = rng.in_unit_disk();
+            assert!(p.magnitude() < 1.0);
+            assert_eq!(p.z, 0.0);
+        }
+    }
+}
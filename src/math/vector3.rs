@@ -36,6 +36,30 @@ impl Vector3 {
         (1.0 - t) * left + t * right
     }
 
+    /// Reflects the vector about the unit `normal`.
+    pub fn reflect(&self, normal: Vector3) -> Self {
+        *self - 2.0 * self.dot(normal) * normal
+    }
+
+    /// Refracts the unit vector through the unit `normal`, where
+    /// `eta_ratio` is the ratio of the refractive indices.
+    pub fn refract(&self, normal: Vector3, eta_ratio: f64) -> Self {
+        let cos_theta = (-*self).dot(normal).min(1.0);
+        let perpendicular = eta_ratio * (*self + cos_theta * normal);
+        let parallel =
+            -(1.0 - perpendicular.dot(perpendicular)).abs().sqrt() * normal;
+
+        perpendicular + parallel
+    }
+
+    /// Returns `true` if the vector is close to zero in every dimension.
+    pub fn near_zero(&self) -> bool {
+        let epsilon = 1e-8;
+        self.x.abs() < epsilon
+            && self.y.abs() < epsilon
+            && self.z.abs() < epsilon
+    }
+
     pub fn color(&self) -> Self {
         Self {
             x: 255.999 * self.x,
@@ -105,6 +129,19 @@ impl ops::Mul<f64> for Vector3 {
     }
 }
 
+impl ops::Mul for Vector3 {
+    type Output = Self;
+
+    /// Multiplies the vectors component-wise.
+    fn mul(self, other: Self) -> Self {
+        Self {
+            x: self.x * other.x,
+            y: self.y * other.y,
+            z: self.z * other.z,
+        }
+    }
+}
+
 impl ops::Mul<Vector3> for f64 {
     type Output = Vector3;
 
@@ -173,6 +210,16 @@ mod tests {
         assert_eq!(s * v, Vector3::new(2.0, 4.0, 6.0));
     }
 
+    #[test]
+    fn mul_vector_component_wise() {
+        let v1 = Vector3::new(1.0, 2.0, 3.0);
+        let v2 = Vector3::new(2.0, 3.0, 4.0);
+        assert_eq!(
+            v1 * v2,
+            Vector3::new(2.0, 6.0, 12.0)
+        );
+    }
+
     #[test]
     fn div_vector() {
         let v = Vector3::new(1.0, 2.0, 3.0);
@@ -231,6 +278,39 @@ mod tests {
         assert_eq!(v.unit().magnitude(), 1.0);
     }
 
+    #[test]
+    fn reflect_vector() {
+        let v = Vector3::new(1.0, -1.0, 0.0);
+        let n = Vector3::new(0.0, 1.0, 0.0);
+        assert_eq!(
+            v.reflect(n),
+            Vector3::new(1.0, 1.0, 0.0)
+        );
+    }
+
+    #[test]
+    fn refract_vector_same_medium() {
+        let mut v = Vector3::new(1.0, -1.0, 0.0);
+        let n = Vector3::new(0.0, 1.0, 0.0);
+        let d = v.unit();
+        assert!((d.refract(n, 1.0) - d).near_zero());
+    }
+
+    #[test]
+    fn refract_vector_bends_towards_normal() {
+        let mut v = Vector3::new(1.0, -1.0, 0.0);
+        let n = Vector3::new(0.0, 1.0, 0.0);
+        let refracted = v.unit().refract(n, 1.0 / 1.5);
+        let sin_theta = refracted.x / refracted.magnitude();
+        assert!((sin_theta - 0.5f64.sqrt() / 1.5).abs() < 1e-12);
+    }
+
+    #[test]
+    fn near_zero_vector() {
+        assert!(Vector3::new(1e-9, -1e-9, 0.0).near_zero());
+        assert!(!Vector3::new(1e-9, 1e-3, 0.0).near_zero());
+    }
+
     #[test]
     fn vector_lerp_half() {
         let v1 = Vector3::new(0.0, 0.0, 0.0);
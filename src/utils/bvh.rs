@@ -0,0 +1,554 @@
+use super::hittable::*;
+use crate::math::aabb::Aabb;
+use crate::math::ray::Ray;
+use std::ops::RangeInclusive;
+
+/// Estimated cost of testing a ray against a node's box.
+const TRAVERSAL_COST: f64 = 1.0;
+/// Estimated cost of testing a ray against an object.
+const INTERSECTION_COST: f64 = 1.0;
+/// Largest leaf the surface area heuristic may choose to keep.
+const MAX_LEAF_SIZE: usize = 8;
+
+type Entry = (Aabb, Box<dyn Hittable>);
+
+/// Strategy used to split the objects of a node in two.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum SplitMethod {
+    /// Splits at the median centroid along the longest axis.
+    Median,
+    /// Surface area heuristic evaluated between `bins` evenly sized
+    /// centroid bins along every axis.
+    Sah { bins: usize },
+}
+
+/// Shape of a built hierarchy.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct BvhStats {
+    pub nodes: usize,
+    pub leaves: usize,
+    /// Number of nodes on the longest path from the root to a leaf.
+    pub depth: usize,
+    pub primitives: usize,
+    pub min_leaf_size: usize,
+    pub max_leaf_size: usize,
+}
+
+impl BvhStats {
+    pub fn average_leaf_size(&self) -> f64 {
+        self.primitives as f64 / self.leaves as f64
+    }
+}
+
+/// Work done while tracing rays through a hierarchy.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct TraversalStats {
+    pub rays: u64,
+    pub box_tests: u64,
+    pub primitive_tests: u64,
+}
+
+impl TraversalStats {
+    pub fn box_tests_per_ray(&self) -> f64 {
+        self.box_tests as f64 / self.rays as f64
+    }
+
+    pub fn primitive_tests_per_ray(&self) -> f64 {
+        self.primitive_tests as f64 / self.rays as f64
+    }
+}
+
+/// Node of a bounding volume hierarchy.
+pub struct BvhNode {
+    bbox: Aabb,
+    children: Children,
+}
+
+enum Children {
+    Leaf(Vec<Box<dyn Hittable>>),
+    Branch(Box<BvhNode>, Box<BvhNode>),
+}
+
+/// Outcome of trying to split a node's objects.
+enum Split {
+    Leaf(Vec<Entry>),
+    Branch(Vec<Entry>, Vec<Entry>),
+}
+
+impl BvhNode {
+    /// Builds a hierarchy by splitting the objects at the median centroid
+    /// along the longest axis.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `objects` is empty or an object has no bounding box.
+    pub fn new(objects: Vec<Box<dyn Hittable>>) -> Self {
+        Self::with_split(objects, SplitMethod::Median)
+    }
+
+    /// Builds a hierarchy using the given split `method`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `objects` is empty or an object has no bounding box.
+    pub fn with_split(
+        objects: Vec<Box<dyn Hittable>>,
+        method: SplitMethod,
+    ) -> Self {
+        let objects = objects
+            .into_iter()
+            .map(|object| {
+                let bbox = object
+                    .bounding_box()
+                    .expect("BVH objects must have a bounding box");
+                (bbox, object)
+            })
+            .collect();
+
+        Self::build(objects, method)
+    }
+
+    fn build(objects: Vec<Entry>, method: SplitMethod) -> Self {
+        let bbox = objects
+            .iter()
+            .map(|(bbox, _)| *bbox)
+            .reduce(|a, b| a.surrounding(&b))
+            .expect("BVH nodes must contain at least one object");
+
+        let split = match method {
+            _ if objects.len() == 1 => Split::Leaf(objects),
+            SplitMethod::Median => Self::split_median(objects),
+            SplitMethod::Sah { bins } => {
+                Self::split_sah(objects, &bbox, bins.max(2))
+            }
+        };
+
+        let children = match split {
+            Split::Leaf(objects) => Children::Leaf(
+                objects.into_iter().map(|(_, object)| object).collect(),
+            ),
+            Split::Branch(left, right) => Children::Branch(
+                Box::new(Self::build(left, method)),
+                Box::new(Self::build(right, method)),
+            ),
+        };
+
+        Self { bbox, children }
+    }
+
+    /// Returns the box around the centroids of the objects.
+    fn centroid_bounds(objects: &[Entry]) -> Aabb {
+        objects
+            .iter()
+            .map(|(bbox, _)| Aabb::new(bbox.centroid(), bbox.centroid()))
+            .reduce(|a, b| a.surrounding(&b))
+            .unwrap()
+    }
+
+    fn split_median(mut objects: Vec<Entry>) -> Split {
+        let axis = Self::centroid_bounds(&objects).longest_axis();
+
+        objects.sort_by(|(a, _), (b, _)| {
+            Aabb::axis(a.centroid(), axis)
+                .total_cmp(&Aabb::axis(b.centroid(), axis))
+        });
+
+        let right = objects.split_off(objects.len() / 2);
+        Split::Branch(objects, right)
+    }
+
+    /// Picks the cheapest split between centroid bins, or a leaf when that
+    /// is cheaper and small enough.
+    fn split_sah(objects: Vec<Entry>, bbox: &Aabb, bins: usize) -> Split {
+        let centroids = Self::centroid_bounds(&objects);
+        let area = bbox.surface_area();
+        let bin_of = |entry: &Entry, axis: usize| {
+            let min = Aabb::axis(centroids.min, axis);
+            let extent = Aabb::axis(centroids.max, axis) - min;
+            let offset = Aabb::axis(entry.0.centroid(), axis) - min;
+
+            ((offset / extent * bins as f64) as usize).min(bins - 1)
+        };
+
+        // Cost, axis and first bin of the right half.
+        let mut best: Option<(f64, usize, usize)> = None;
+
+        for axis in 0..3 {
+            let extent = Aabb::axis(centroids.max, axis)
+                - Aabb::axis(centroids.min, axis);
+            if extent <= 0.0 || area <= 0.0 {
+                continue;
+            }
+
+            let mut counts = vec![0; bins];
+            let mut boxes: Vec<Option<Aabb>> = vec![None; bins];
+            for entry in objects.iter() {
+                let bin = bin_of(entry, axis);
+                counts[bin] += 1;
+                boxes[bin] = Some(match boxes[bin] {
+                    Some(bbox) => bbox.surrounding(&entry.0),
+                    None => entry.0,
+                });
+            }
+
+            // Sweep from the right so every split sees its right half.
+            let mut right_area = vec![0.0; bins];
+            let mut right_count = vec![0; bins];
+            let mut right_box: Option<Aabb> = None;
+            let mut count = 0;
+            for bin in (1..bins).rev() {
+                right_box = Self::merge(right_box, boxes[bin]);
+                count += counts[bin];
+                right_area[bin] = right_box.map_or(0.0, |b| b.surface_area());
+                right_count[bin] = count;
+            }
+
+            let mut left_box: Option<Aabb> = None;
+            let mut left_count = 0;
+            for bin in 1..bins {
+                left_box = Self::merge(left_box, boxes[bin - 1]);
+                left_count += counts[bin - 1];
+                if left_count == 0 || right_count[bin] == 0 {
+                    continue;
+                }
+
+                let left_area = left_box.map_or(0.0, |b| b.surface_area());
+                let cost = TRAVERSAL_COST
+                    + INTERSECTION_COST
+                        * (left_area * left_count as f64
+                            + right_area[bin] * right_count[bin] as f64)
+                        / area;
+
+                if best.is_none_or(|(best_cost, _, _)| cost < best_cost) {
+                    best = Some((cost, axis, bin));
+                }
+            }
+        }
+
+        let leaf_cost = INTERSECTION_COST * objects.len() as f64;
+        match best {
+            Some((cost, _, _))
+                if cost >= leaf_cost && objects.len() <= MAX_LEAF_SIZE =>
+            {
+                Split::Leaf(objects)
+            }
+            Some((_, axis, split)) => {
+                let (left, right) = objects
+                    .into_iter()
+                    .partition(|entry| bin_of(entry, axis) < split);
+                Split::Branch(left, right)
+            }
+            // Every centroid is in the same place, so no plane separates
+            // the objects.
+            None if objects.len() <= MAX_LEAF_SIZE => Split::Leaf(objects),
+            None => Self::split_median(objects),
+        }
+    }
+
+    fn merge(a: Option<Aabb>, b: Option<Aabb>) -> Option<Aabb> {
+        match (a, b) {
+            (Some(a), Some(b)) => Some(a.surrounding(&b)),
+            (a, None) => a,
+            (None, b) => b,
+        }
+    }
+
+    /// Returns the objects in the leaves from left to right.
+    pub fn objects(&self) -> Vec<&dyn Hittable> {
+        match &self.children {
+            Children::Leaf(objects) => {
+                objects.iter().map(|object| &**object).collect()
+            }
+            Children::Branch(left, right) => {
+                let mut objects = left.objects();
+                objects.extend(right.objects());
+                objects
+            }
+        }
+    }
+
+    /// Walks the hierarchy and reports its shape.
+    pub fn stats(&self) -> BvhStats {
+        match &self.children {
+            Children::Leaf(objects) => BvhStats {
+                nodes: 1,
+                leaves: 1,
+                depth: 1,
+                primitives: objects.len(),
+                min_leaf_size: objects.len(),
+                max_leaf_size: objects.len(),
+            },
+            Children::Branch(left, right) => {
+                let left = left.stats();
+                let right = right.stats();
+
+                BvhStats {
+                    nodes: left.nodes + right.nodes + 1,
+                    leaves: left.leaves + right.leaves,
+                    depth: left.depth.max(right.depth) + 1,
+                    primitives: left.primitives + right.primitives,
+                    min_leaf_size: left.min_leaf_size.min(right.min_leaf_size),
+                    max_leaf_size: left.max_leaf_size.max(right.max_leaf_size),
+                }
+            }
+        }
+    }
+
+    /// Traces every ray through the hierarchy and counts the box and
+    /// object tests it needed.
+    pub fn measure(
+        &self,
+        rays: &[Ray],
+        valid_range: RangeInclusive<f64>,
+    ) -> TraversalStats {
+        let mut stats = TraversalStats::default();
+
+        for ray in rays {
+            stats.rays += 1;
+            self.traverse(
+                ray,
+                valid_range.clone(),
+                &mut stats,
+            );
+        }
+
+        stats
+    }
+
+    fn traverse(
+        &self,
+        ray: &Ray,
+        valid_range: RangeInclusive<f64>,
+        stats: &mut TraversalStats,
+    ) -> Option<Hit<'_>> {
+        stats.box_tests += 1;
+        if !self.bbox.hit(ray, valid_range.clone()) {
+            return None;
+        }
+
+        match &self.children {
+            Children::Leaf(objects) => {
+                let mut closest = *valid_range.end();
+                let mut hit_anything = None;
+
+                for object in objects.iter() {
+                    stats.primitive_tests += 1;
+                    if let Some(hit) =
+                        object.hit(ray, *valid_range.start()..=closest)
+                    {
+                        closest = hit.t;
+                        hit_anything = Some(hit);
+                    }
+                }
+
+                hit_anything
+            }
+            Children::Branch(left, right) => {
+                let left_hit = left.traverse(ray, valid_range.clone(), stats);
+                let closest = match &left_hit {
+                    Some(hit) => hit.t,
+                    None => *valid_range.end(),
+                };
+
+                right
+                    .traverse(
+                        ray,
+                        *valid_range.start()..=closest,
+                        stats,
+                    )
+                    .or(left_hit)
+            }
+        }
+    }
+}
+
+impl Hittable for BvhNode {
+    /// Skips the node if the ray misses its box, otherwise returns the
+    /// closest `Hit` of its children.
+    fn hit(
+        &self,
+        ray: &Ray,
+        valid_range: RangeInclusive<f64>,
+    ) -> Option<Hit<'_>> {
+        self.traverse(
+            ray,
+            valid_range,
+            &mut TraversalStats::default(),
+        )
+    }
+
+    fn bounding_box(&self) -> Option<Aabb> {
+        Some(self.bbox)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::math::vector3::Vector3;
+    use crate::utils::material::Normal;
+    use crate::utils::sphere::Sphere;
+    use crate::utils::world::World;
+    use std::sync::Arc;
+
+    fn spheres() -> Vec<Box<dyn Hittable>> {
+        let material = Arc::new(Normal);
+        let mut objects: Vec<Box<dyn Hittable>> = Vec::new();
+
+        for i in 0..10 {
+            for j in 0..10 {
+                objects.push(Box::new(Sphere::new(
+                    Vector3::new(i as f64, j as f64, -(i + j) as f64),
+                    0.4,
+                    material.clone(),
+                )));
+            }
+        }
+
+        objects
+    }
+
+    fn rays() -> Vec<Ray> {
+        let mut rays = Vec::new();
+
+        for i in 0..50 {
+            for j in 0..50 {
+                rays.push(Ray::new(
+                    Vector3::new(4.5, 4.5, 10.0),
+                    Vector3::new(
+                        i as f64 / 5.0 - 5.0,
+                        j as f64 / 5.0 - 5.0,
+                        -10.0,
+                    ),
+                ));
+            }
+        }
+
+        rays
+    }
+
+    fn assert_matches_linear_search(bvh: &BvhNode) {
+        let mut world = World::default();
+        for object in spheres() {
+            world.add(object);
+        }
+
+        for ray in rays() {
+            let expected = world.hit(&ray, 0.001..=f64::MAX);
+            let actual = bvh.hit(&ray, 0.001..=f64::MAX);
+            assert_eq!(
+                expected.map(|hit| hit.t),
+                actual.map(|hit| hit.t)
+            );
+        }
+    }
+
+    #[test]
+    fn bvh_matches_linear_search() {
+        assert_matches_linear_search(&BvhNode::new(spheres()));
+    }
+
+    #[test]
+    fn sah_matches_linear_search() {
+        assert_matches_linear_search(&BvhNode::with_split(
+            spheres(),
+            SplitMethod::Sah { bins: 16 },
+        ));
+    }
+
+    #[test]
+    fn bvh_bounds_every_object() {
+        let bbox = BvhNode::new(spheres()).bounding_box().unwrap();
+
+        assert!((bbox.min - Vector3::new(-0.4, -0.4, -18.4)).near_zero());
+        assert!((bbox.max - Vector3::new(9.4, 9.4, 0.4)).near_zero());
+    }
+
+    #[test]
+    fn median_stats() {
+        let stats = BvhNode::new(spheres()).stats();
+
+        assert_eq!(
+            stats,
+            BvhStats {
+                nodes: 199,
+                leaves: 100,
+                depth: 8,
+                primitives: 100,
+                min_leaf_size: 1,
+                max_leaf_size: 1,
+            }
+        );
+        assert_eq!(stats.average_leaf_size(), 1.0);
+    }
+
+    #[test]
+    fn sah_stats() {
+        let stats = BvhNode::with_split(
+            spheres(),
+            SplitMethod::Sah { bins: 16 },
+        )
+        .stats();
+
+        assert_eq!(stats.primitives, 100);
+        assert_eq!(stats.nodes, 2 * stats.leaves - 1);
+        assert!(stats.max_leaf_size <= MAX_LEAF_SIZE);
+    }
+
+    #[test]
+    fn sah_keeps_coincident_objects_together() {
+        let material = Arc::new(Normal);
+        let objects: Vec<Box<dyn Hittable>> = (0..4)
+            .map(|_| {
+                Box::new(Sphere::new(
+                    Vector3::new(0.0, 0.0, 0.0),
+                    1.0,
+                    material.clone(),
+                )) as Box<dyn Hittable>
+            })
+            .collect();
+
+        let stats = BvhNode::with_split(
+            objects,
+            SplitMethod::Sah { bins: 16 },
+        )
+        .stats();
+        assert_eq!(stats.nodes, 1);
+        assert_eq!(stats.max_leaf_size, 4);
+    }
+
+    #[test]
+    fn measure_counts_tests() {
+        let bvh = BvhNode::new(spheres());
+        let rays = rays();
+        let stats = bvh.measure(&rays, 0.001..=f64::MAX);
+
+        assert_eq!(stats.rays, rays.len() as u64);
+        assert!(stats.box_tests_per_ray() >= 1.0);
+        assert!(stats.primitive_tests_per_ray() < 100.0);
+    }
+
+    #[test]
+    fn measure_single_object() {
+        let bvh = BvhNode::new(spheres().into_iter().take(1).collect());
+        let ray = Ray::new(
+            Vector3::new(0.0, 0.0, 5.0),
+            Vector3::new(0.0, 0.0, -1.0),
+        );
+
+        assert_eq!(
+            bvh.measure(&[ray], 0.001..=f64::MAX),
+            TraversalStats {
+                rays: 1,
+                box_tests: 1,
+                primitive_tests: 1,
+            }
+        );
+    }
+
+    #[test]
+    #[should_panic]
+    fn empty_bvh_panics() {
+        BvhNode::new(Vec::new());
+    }
+}
@@ -1,3 +1,5 @@
+use crate::math::random::Rng;
+use crate::math::ray::Ray;
 use crate::math::vector3::Vector3;
 
 /// Stores data for the camera
@@ -10,22 +12,425 @@ pub struct Camera {
 
     pub horizontal: Vector3,
     pub vertical: Vector3,
+    pub lower_left_corner: Vector3,
+
+    /// Orthonormal basis of the camera, `w` points away from the scene.
+    pub u: Vector3,
+    pub v: Vector3,
+    pub w: Vector3,
+
+    /// Radius of the thin lens, zero for a pinhole camera.
+    pub lens_radius: f64,
+    /// Distance to the plane that is in perfect focus.
+    pub focus_distance: f64,
 }
 
 impl Camera {
+    /// Creates a pinhole camera at `position` looking down the negative z
+    /// axis.
     pub fn new(
         viewport_height: f64,
         viewport_width: f64,
         focal_length: f64,
         position: Vector3,
     ) -> Self {
+        let horizontal = Vector3::new(viewport_width, 0.0, 0.0);
+        let vertical = Vector3::new(0.0, viewport_height, 0.0);
+
         Self {
             viewport_height,
             viewport_width,
             focal_length,
             position,
-            horizontal: Vector3::new(viewport_width, 0.0, 0.0),
-            vertical: Vector3::new(0.0, viewport_height, 0.0),
+            horizontal,
+            vertical,
+            lower_left_corner: position
+                - horizontal / 2.0
+                - vertical / 2.0
+                - Vector3::new(0.0, 0.0, focal_length),
+            u: Vector3::new(1.0, 0.0, 0.0),
+            v: Vector3::new(0.0, 1.0, 0.0),
+            w: Vector3::new(0.0, 0.0, 1.0),
+            lens_radius: 0.0,
+            focus_distance: focal_length,
+        }
+    }
+
+    /// Creates a camera at `look_from` pointed at `look_at`, with `up`
+    /// giving the roll and `vertical_fov` in degrees.
+    ///
+    /// Objects at `focus_distance` are sharp, everything else is blurred
+    /// more the larger the `aperture` is. `look_from` and `look_at` must
+    /// differ and `up` must not be parallel to the view direction, see
+    /// `CameraSettings::validate`.
+    pub fn look_at(
+        look_from: Vector3,
+        look_at: Vector3,
+        mut up: Vector3,
+        vertical_fov: f64,
+        aspect_ratio: f64,
+        aperture: f64,
+        focus_distance: f64,
+    ) -> Self {
+        let theta = vertical_fov.to_radians();
+        let viewport_height = 2.0 * (theta / 2.0).tan();
+        let viewport_width = aspect_ratio * viewport_height;
+
+        let mut w = (look_from - look_at).unit();
+        let mut u = up.cross(w);
+        debug_assert!(
+            !u.near_zero() && u.x.is_finite(),
+            "degenerate camera basis"
+        );
+        let u = u.unit();
+        let v = w.cross(u);
+
+        // The viewport sits on the focus plane.
+        let horizontal = focus_distance * viewport_width * u;
+        let vertical = focus_distance * viewport_height * v;
+
+        Self {
+            viewport_height,
+            viewport_width,
+            focal_length: 1.0,
+            position: look_from,
+            horizontal,
+            vertical,
+            lower_left_corner: look_from
+                - horizontal / 2.0
+                - vertical / 2.0
+                - focus_distance * w,
+            u,
+            v,
+            w,
+            lens_radius: aperture / 2.0,
+            focus_distance,
+        }
+    }
+
+    /// Returns the ray through the viewport at horizontal offset `s` and
+    /// vertical offset `t`, both in `[0, 1]` from the lower left corner.
+    ///
+    /// The ray starts from a random point on the lens, a pinhole camera
+    /// doesn't draw from `rng`.
+    pub fn get_ray(&self, s: f64, t: f64, rng: &mut Rng) -> Ray {
+        let offset = if self.lens_radius == 0.0 {
+            Vector3::new(0.0, 0.0, 0.0)
+        } else {
+            let lens = self.lens_radius * rng.in_unit_disk();
+            lens.x * self.u + lens.y * self.v
+        };
+
+        Ray::new(
+            self.position + offset,
+            self.lower_left_corner + s * self.horizontal + t * self.vertical
+                - self.position
+                - offset,
+        )
+    }
+}
+
+/// Where the camera is and how it is set up, independent of the image
+/// aspect ratio.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct CameraSettings {
+    pub look_from: Vector3,
+    pub look_at: Vector3,
+    pub up: Vector3,
+    /// Vertical field of view in degrees.
+    pub vertical_fov: f64,
+    pub aperture: f64,
+    /// `None` focuses on `look_at`.
+    pub focus_distance: Option<f64>,
+}
+
+impl Default for CameraSettings {
+    /// A pinhole camera at the origin looking down the negative z axis.
+    fn default() -> Self {
+        Self {
+            look_from: Vector3::new(0.0, 0.0, 0.0),
+            look_at: Vector3::new(0.0, 0.0, -1.0),
+            up: Vector3::new(0.0, 1.0, 0.0),
+            vertical_fov: 90.0,
+            aperture: 0.0,
+            focus_distance: None,
+        }
+    }
+}
+
+impl CameraSettings {
+    /// Checks that the settings describe a camera with a well defined
+    /// orientation and a lens it can render through.
+    pub fn validate(&self) -> Result<(), String> {
+        let finite = |vector: Vector3| {
+            vector.x.is_finite()
+                && vector.y.is_finite()
+                && vector.z.is_finite()
+        };
+        if !(finite(self.look_from) && finite(self.look_at) && finite(self.up))
+        {
+            return Err("the camera vectors must be finite".to_string());
+        }
+
+        let mut forward = self.look_at - self.look_from;
+        if forward.near_zero() {
+            return Err("the camera looks at its own position".to_string());
+        }
+
+        let mut up = self.up;
+        if up.near_zero() || up.unit().cross(forward.unit()).near_zero() {
+            return Err("`up` is parallel to the view direction".to_string());
+        }
+
+        if !(self.vertical_fov > 0.0 && self.vertical_fov < 180.0) {
+            return Err(
+                "the field of view must be between 0 and 180 degrees"
+                    .to_string(),
+            );
+        }
+        if !(self.aperture >= 0.0 && self.aperture.is_finite()) {
+            return Err("the aperture must not be negative".to_string());
+        }
+        if let Some(distance) = self.focus_distance {
+            if !(distance > 0.0 && distance.is_finite()) {
+                return Err("the focus distance must be positive".to_string());
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Builds the camera, the settings must be valid.
+    pub fn build(&self, aspect_ratio: f64) -> Camera {
+        Camera::look_at(
+            self.look_from,
+            self.look_at,
+            self.up,
+            self.vertical_fov,
+            aspect_ratio,
+            self.aperture,
+            self.focus_distance.unwrap_or_else(|| {
+                (self.look_from - self.look_at).magnitude()
+            }),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_close(a: Vector3, b: Vector3) {
+        assert!(
+            (a - b).near_zero(),
+            "{:?} != {:?}",
+            a,
+            b
+        );
+    }
+
+    #[test]
+    fn look_at_down_negative_z() {
+        let camera = Camera::look_at(
+            Vector3::new(0.0, 0.0, 0.0),
+            Vector3::new(0.0, 0.0, -1.0),
+            Vector3::new(0.0, 1.0, 0.0),
+            90.0,
+            2.0,
+            0.0,
+            1.0,
+        );
+        let reference = Camera::new(
+            2.0,
+            4.0,
+            1.0,
+            Vector3::new(0.0, 0.0, 0.0),
+        );
+
+        assert_close(
+            camera.horizontal,
+            reference.horizontal,
+        );
+        assert_close(camera.vertical, reference.vertical);
+        assert_close(camera.w, reference.w);
+    }
+
+    #[test]
+    fn get_ray_corners() {
+        let camera = Camera::new(
+            2.0,
+            4.0,
+            1.0,
+            Vector3::new(0.0, 0.0, 0.0),
+        );
+        let mut rng = Rng::default();
+
+        assert_eq!(
+            camera.get_ray(0.0, 0.0, &mut rng).direction,
+            Vector3::new(-2.0, -1.0, -1.0)
+        );
+        assert_eq!(
+            camera.get_ray(0.5, 0.5, &mut rng).direction,
+            Vector3::new(0.0, 0.0, -1.0)
+        );
+        assert_eq!(
+            camera.get_ray(1.0, 1.0, &mut rng).direction,
+            Vector3::new(2.0, 1.0, -1.0)
+        );
+    }
+
+    #[test]
+    fn pinhole_does_not_sample_lens() {
+        let camera = Camera::new(
+            2.0,
+            4.0,
+            1.0,
+            Vector3::new(0.0, 0.0, 0.0),
+        );
+        let mut rng = Rng::new(7);
+        let mut untouched = rng.clone();
+
+        camera.get_ray(0.3, 0.7, &mut rng);
+        assert_eq!(
+            rng.next_u64(),
+            untouched.next_u64()
+        );
+    }
+
+    #[test]
+    fn defocus_rays_meet_on_focus_plane() {
+        let camera = Camera::look_at(
+            Vector3::new(0.0, 0.0, 0.0),
+            Vector3::new(0.0, 0.0, -1.0),
+            Vector3::new(0.0, 1.0, 0.0),
+            90.0,
+            1.0,
+            0.5,
+            4.0,
+        );
+        let mut rng = Rng::default();
+        let focus = camera.get_ray(0.3, 0.7, &mut rng).at(1.0);
+
+        for _ in 0..100 {
+            let ray = camera.get_ray(0.3, 0.7, &mut rng);
+            let lens = ray.origin - camera.position;
+
+            assert!(lens.magnitude() < camera.lens_radius);
+            assert!(lens.dot(camera.w).abs() < 1e-12);
+            assert_close(ray.at(1.0), focus);
         }
     }
+
+    #[test]
+    fn look_at_basis_is_orthonormal() {
+        let camera = Camera::look_at(
+            Vector3::new(-2.0, 2.0, 1.0),
+            Vector3::new(0.0, 0.0, -1.0),
+            Vector3::new(0.0, 1.0, 0.0),
+            20.0,
+            16.0 / 9.0,
+            2.0,
+            3.0,
+        );
+
+        assert!((camera.u.magnitude() - 1.0).abs() < 1e-12);
+        assert!((camera.v.magnitude() - 1.0).abs() < 1e-12);
+        assert!((camera.w.magnitude() - 1.0).abs() < 1e-12);
+        assert!(camera.u.dot(camera.v).abs() < 1e-12);
+        assert!(camera.v.dot(camera.w).abs() < 1e-12);
+        assert!(camera.w.dot(camera.u).abs() < 1e-12);
+    }
+
+    #[test]
+    fn degenerate_settings() {
+        assert!(CameraSettings::default().validate().is_ok());
+
+        let settings = CameraSettings {
+            look_from: Vector3::new(1.0, 2.0, 3.0),
+            look_at: Vector3::new(1.0, 2.0, 3.0),
+            ..Default::default()
+        };
+        assert_eq!(
+            settings.validate(),
+            Err("the camera looks at its own position".to_string())
+        );
+
+        for up in [
+            Vector3::new(0.0, 0.0, 2.0),
+            Vector3::new(0.0, 0.0, -1.0),
+            Vector3::new(0.0, 0.0, 0.0),
+        ] {
+            let settings = CameraSettings {
+                up,
+                ..Default::default()
+            };
+            assert_eq!(
+                settings.validate(),
+                Err("`up` is parallel to the view direction".to_string())
+            );
+        }
+
+        let settings = CameraSettings {
+            look_from: Vector3::new(f64::NAN, 0.0, 0.0),
+            ..Default::default()
+        };
+        assert_eq!(
+            settings.validate(),
+            Err("the camera vectors must be finite".to_string())
+        );
+
+        for vertical_fov in [0.0, -10.0, 180.0, f64::NAN] {
+            let settings = CameraSettings {
+                vertical_fov,
+                ..Default::default()
+            };
+            assert_eq!(
+                settings.validate(),
+                Err(
+                    "the field of view must be between 0 and 180 degrees"
+                        .to_string()
+                )
+            );
+        }
+
+        for aperture in [-0.1, f64::NAN] {
+            let settings = CameraSettings {
+                aperture,
+                ..Default::default()
+            };
+            assert_eq!(
+                settings.validate(),
+                Err("the aperture must not be negative".to_string())
+            );
+        }
+
+        for focus_distance in [0.0, -1.0, f64::NAN] {
+            let settings = CameraSettings {
+                focus_distance: Some(focus_distance),
+                ..Default::default()
+            };
+            assert_eq!(
+                settings.validate(),
+                Err("the focus distance must be positive".to_string())
+            );
+        }
+    }
+
+    #[test]
+    fn look_at_points_at_target() {
+        let look_from = Vector3::new(3.0, 3.0, 2.0);
+        let look_at = Vector3::new(0.0, 0.0, -1.0);
+        let camera = Camera::look_at(
+            look_from,
+            look_at,
+            Vector3::new(0.0, 1.0, 0.0),
+            40.0,
+            1.0,
+            0.0,
+            1.0,
+        );
+
+        let mut forward = look_at - look_from;
+        assert_close(-camera.w, forward.unit());
+    }
 }
@@ -0,0 +1,99 @@
+/// Transfer function used to encode linear radiance for display.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Transfer {
+    /// Writes linear values unchanged.
+    Linear,
+    /// The piecewise sRGB curve.
+    Srgb,
+    /// A pure power curve, `Gamma(2.2)` raises values to `1 / 2.2`.
+    Gamma(f64),
+}
+
+impl Transfer {
+    /// Encodes a linear value, clamping it to `[0, 1]` first.
+    pub fn encode(&self, value: f64) -> f64 {
+        let value = value.clamp(0.0, 1.0);
+
+        match self {
+            Transfer::Linear => value,
+            Transfer::Srgb => {
+                if value <= 0.003_130_8 {
+                    12.92 * value
+                } else {
+                    1.055 * value.powf(1.0 / 2.4) - 0.055
+                }
+            }
+            Transfer::Gamma(gamma) => value.powf(1.0 / gamma),
+        }
+    }
+
+    /// Decodes an encoded value back to linear.
+    pub fn decode(&self, value: f64) -> f64 {
+        let value = value.clamp(0.0, 1.0);
+
+        match self {
+            Transfer::Linear => value,
+            Transfer::Srgb => {
+                if value <= 0.040_45 {
+                    value / 12.92
+                } else {
+                    ((value + 0.055) / 1.055).powf(2.4)
+                }
+            }
+            Transfer::Gamma(gamma) => value.powf(*gamma),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn srgb_end_points() {
+        assert_eq!(Transfer::Srgb.encode(0.0), 0.0);
+        assert!((Transfer::Srgb.encode(1.0) - 1.0).abs() < 1e-12);
+    }
+
+    #[test]
+    fn srgb_middle_gray() {
+        // 18% gray is encoded at roughly 46%.
+        assert!((Transfer::Srgb.encode(0.18) - 0.4614).abs() < 1e-4);
+    }
+
+    #[test]
+    fn srgb_linear_segment() {
+        assert!((Transfer::Srgb.encode(0.001) - 0.01292).abs() < 1e-12);
+    }
+
+    #[test]
+    fn gamma_curve() {
+        assert_eq!(
+            Transfer::Gamma(2.0).encode(0.25),
+            0.5
+        );
+    }
+
+    #[test]
+    fn encode_clamps() {
+        assert_eq!(Transfer::Srgb.encode(-1.0), 0.0);
+        assert_eq!(
+            Transfer::Gamma(2.2).encode(4.0),
+            1.0
+        );
+        assert!(Transfer::Linear.encode(f64::NAN).is_nan());
+    }
+
+    #[test]
+    fn decode_inverts_encode() {
+        for transfer in
+            [Transfer::Linear, Transfer::Srgb, Transfer::Gamma(2.2)]
+        {
+            for i in 0..=100 {
+                let value = i as f64 / 100.0;
+                let round_trip = transfer.decode(transfer.encode(value));
+                assert!((round_trip - value).abs() < 1e-12);
+            }
+        }
+    }
+}
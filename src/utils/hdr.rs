@@ -0,0 +1,630 @@
+use super::image::HdrImage;
+use crate::math::vector3::Vector3;
+use std::io::Read;
+use std::io::Write;
+
+/// Error returned when a Radiance or PFM file can't be read.
+#[derive(Debug)]
+pub enum HdrError {
+    Io(std::io::Error),
+    /// The file is not in a supported format or orientation.
+    UnsupportedFormat(String),
+    /// A header field is missing or malformed.
+    InvalidHeader(String),
+    /// The run length encoded pixel data is corrupt.
+    InvalidData(String),
+    /// The file ends before every pixel was read.
+    Truncated {
+        expected: usize,
+        found: usize,
+    },
+}
+
+impl std::fmt::Display for HdrError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            HdrError::Io(error) => write!(f, "{}", error),
+            HdrError::UnsupportedFormat(format) => {
+                write!(
+                    f,
+                    "unsupported HDR format `{}`",
+                    format
+                )
+            }
+            HdrError::InvalidHeader(message) => {
+                write!(
+                    f,
+                    "invalid HDR header: {}",
+                    message
+                )
+            }
+            HdrError::InvalidData(message) => {
+                write!(f, "invalid HDR data: {}", message)
+            }
+            HdrError::Truncated { expected, found } => write!(
+                f,
+                "truncated HDR data: expected {} bytes, found {}",
+                expected, found
+            ),
+        }
+    }
+}
+
+impl std::error::Error for HdrError {}
+
+impl From<std::io::Error> for HdrError {
+    fn from(error: std::io::Error) -> Self {
+        HdrError::Io(error)
+    }
+}
+
+/// Encodes linear radiance as a shared exponent RGBE pixel.
+pub fn to_rgbe(radiance: Vector3) -> [u8; 4] {
+    let brightest = radiance.x.max(radiance.y).max(radiance.z);
+    if brightest.is_nan() || brightest < 1e-32 {
+        return [0, 0, 0, 0];
+    }
+    // The largest value RGBE holds has a full mantissa and the exponent
+    // byte at 255, anything brighter would overflow the exponent.
+    let brightest = brightest.min(255.0 / 256.0 * 2f64.powi(127));
+
+    // Split into a mantissa in [0.5, 1) and an exponent.
+    let mut exponent = brightest.log2().floor() as i32 + 1;
+    let mut mantissa = brightest / 2f64.powi(exponent);
+    if mantissa >= 1.0 {
+        mantissa /= 2.0;
+        exponent += 1;
+    } else if mantissa < 0.5 {
+        mantissa *= 2.0;
+        exponent -= 1;
+    }
+
+    let scale = mantissa * 256.0 / brightest;
+    let channel = |value: f64| (value.max(0.0) * scale) as u8;
+
+    [
+        channel(radiance.x),
+        channel(radiance.y),
+        channel(radiance.z),
+        (exponent + 128) as u8,
+    ]
+}
+
+/// Decodes an RGBE pixel to linear radiance.
+pub fn from_rgbe(rgbe: [u8; 4]) -> Vector3 {
+    if rgbe[3] == 0 {
+        return Vector3::new(0.0, 0.0, 0.0);
+    }
+
+    let scale = 2f64.powi(rgbe[3] as i32 - (128 + 8));
+    Vector3::new(
+        rgbe[0] as f64 * scale,
+        rgbe[1] as f64 * scale,
+        rgbe[2] as f64 * scale,
+    )
+}
+
+impl HdrImage {
+    /// Writes the image as an uncompressed Radiance `.hdr` file.
+    pub fn write_hdr<W: Write>(&self, mut writer: W) -> std::io::Result<()> {
+        write!(
+            writer,
+            "#?RADIANCE\nFORMAT=32-bit_rle_rgbe\n\n-Y {} +X {}\n",
+            self.rows(),
+            self.columns()
+        )?;
+
+        for pixel in self.pixels.iter() {
+            writer.write_all(&to_rgbe(*pixel))?;
+        }
+
+        writer.flush()
+    }
+
+    /// Reads a Radiance `.hdr` file with flat or run length encoded
+    /// scanlines.
+    pub fn read_hdr<R: Read>(mut reader: R) -> Result<Self, HdrError> {
+        let mut bytes = Vec::new();
+        reader.read_to_end(&mut bytes)?;
+
+        Self::parse_hdr(&bytes)
+    }
+
+    pub fn parse_hdr(bytes: &[u8]) -> Result<Self, HdrError> {
+        let mut lines = Lines { bytes, position: 0 };
+
+        let magic = lines.next().unwrap_or_default();
+        if magic != "#?RADIANCE" && magic != "#?RGBE" {
+            return Err(HdrError::UnsupportedFormat(
+                magic.to_string(),
+            ));
+        }
+
+        // Header variables end at the first blank line.
+        loop {
+            let line = lines.next().ok_or_else(|| {
+                HdrError::InvalidHeader("missing resolution".to_string())
+            })?;
+
+            if line.is_empty() {
+                break;
+            }
+            if let Some(format) = line.strip_prefix("FORMAT=") {
+                if format != "32-bit_rle_rgbe" {
+                    return Err(HdrError::UnsupportedFormat(
+                        format.to_string(),
+                    ));
+                }
+            }
+        }
+
+        let resolution = lines.next().unwrap_or_default();
+        let (rows, columns) =
+            match resolution.split_whitespace().collect::<Vec<_>>()[..] {
+                ["-Y", rows, "+X", columns] => (
+                    parse_dimension(rows, "height")?,
+                    parse_dimension(columns, "width")?,
+                ),
+                _ => {
+                    return Err(HdrError::UnsupportedFormat(
+                        format!("resolution `{}`", resolution),
+                    ))
+                }
+            };
+
+        let mut data = &bytes[lines.position..];
+        // Even run length encoded pixels take at least a byte per channel
+        // for every 128 pixels.
+        if pixel_count(columns, rows)? / 128 > data.len() {
+            return Err(HdrError::Truncated {
+                expected: pixel_count(columns, rows)? / 128,
+                found: data.len(),
+            });
+        }
+
+        let mut image = HdrImage::new(columns as u64, rows as u64);
+        let mut scanline = vec![[0u8; 4]; columns];
+
+        for row in image.pixels.chunks_mut(columns.max(1)) {
+            data = read_scanline(data, &mut scanline)?;
+            for (pixel, rgbe) in row.iter_mut().zip(scanline.iter()) {
+                *pixel = from_rgbe(*rgbe);
+            }
+        }
+
+        Ok(image)
+    }
+
+    /// Writes the image as a little endian color Portable FloatMap.
+    pub fn write_pfm<W: Write>(&self, mut writer: W) -> std::io::Result<()> {
+        write!(
+            writer,
+            "PF\n{} {}\n-1.0\n",
+            self.columns(),
+            self.rows()
+        )?;
+
+        // Scanlines are stored from the bottom up.
+        for row in self.pixels.chunks(self.columns().max(1)).rev() {
+            for pixel in row {
+                for value in [pixel.x, pixel.y, pixel.z] {
+                    writer.write_all(&(value as f32).to_le_bytes())?;
+                }
+            }
+        }
+
+        writer.flush()
+    }
+
+    /// Reads a color `PF` or grayscale `Pf` Portable FloatMap of either
+    /// endianness.
+    pub fn read_pfm<R: Read>(mut reader: R) -> Result<Self, HdrError> {
+        let mut bytes = Vec::new();
+        reader.read_to_end(&mut bytes)?;
+
+        Self::parse_pfm(&bytes)
+    }
+
+    pub fn parse_pfm(bytes: &[u8]) -> Result<Self, HdrError> {
+        let mut position = 0;
+        let mut token = || {
+            while bytes.get(position).is_some_and(u8::is_ascii_whitespace) {
+                position += 1;
+            }
+            let start = position;
+            while bytes
+                .get(position)
+                .is_some_and(|byte| !byte.is_ascii_whitespace())
+            {
+                position += 1;
+            }
+
+            String::from_utf8_lossy(&bytes[start..position]).into_owned()
+        };
+
+        let channels = match token().as_str() {
+            "PF" => 3,
+            "Pf" => 1,
+            magic => {
+                return Err(HdrError::UnsupportedFormat(
+                    magic.into(),
+                ))
+            }
+        };
+        let columns = parse_dimension(&token(), "width")?;
+        let rows = parse_dimension(&token(), "height")?;
+        let scale = token();
+        let little_endian = match scale.parse::<f64>() {
+            Ok(scale) if scale < 0.0 => true,
+            Ok(scale) if scale > 0.0 => false,
+            _ => {
+                return Err(HdrError::InvalidHeader(format!(
+                    "scale `{}` is not a non-zero number",
+                    scale
+                )))
+            }
+        };
+
+        // A single whitespace byte separates the header from the data.
+        let data = bytes.get(position + 1..).unwrap_or_default();
+        let expected = pixel_count(columns, rows)? * channels * 4;
+        if data.len() < expected {
+            return Err(HdrError::Truncated {
+                expected,
+                found: data.len(),
+            });
+        }
+
+        let values: Vec<f64> = data[..expected]
+            .chunks_exact(4)
+            .map(|bytes| {
+                let bytes = bytes.try_into().unwrap();
+                if little_endian {
+                    f32::from_le_bytes(bytes) as f64
+                } else {
+                    f32::from_be_bytes(bytes) as f64
+                }
+            })
+            .collect();
+
+        let mut image = HdrImage::new(columns as u64, rows as u64);
+        let scanlines = values.chunks(columns.max(1) * channels).rev();
+        for (row, scanline) in
+            image.pixels.chunks_mut(columns.max(1)).zip(scanlines)
+        {
+            for (pixel, values) in
+                row.iter_mut().zip(scanline.chunks(channels))
+            {
+                *pixel = match values {
+                    [r, g, b] => Vector3::new(*r, *g, *b),
+                    [gray] => Vector3::new(*gray, *gray, *gray),
+                    _ => unreachable!(),
+                };
+            }
+        }
+
+        Ok(image)
+    }
+}
+
+/// Returns the number of pixels, or an error if it would overflow.
+fn pixel_count(columns: usize, rows: usize) -> Result<usize, HdrError> {
+    columns
+        .checked_mul(rows)
+        .filter(|pixels| pixels.checked_mul(16).is_some())
+        .ok_or_else(|| {
+            HdrError::InvalidHeader(format!(
+                "{}x{} is too large",
+                columns, rows
+            ))
+        })
+}
+
+/// Parses a width or height, which must be at least one pixel.
+fn parse_dimension(token: &str, field: &str) -> Result<usize, HdrError> {
+    let dimension = token.parse().map_err(|_| {
+        HdrError::InvalidHeader(format!(
+            "{} `{}` is not a number",
+            field, token
+        ))
+    })?;
+    if dimension == 0 {
+        return Err(HdrError::InvalidHeader(format!(
+            "{} must not be zero",
+            field
+        )));
+    }
+
+    Ok(dimension)
+}
+
+/// Splits the text header of a Radiance file into lines.
+struct Lines<'a> {
+    bytes: &'a [u8],
+    position: usize,
+}
+
+impl<'a> Lines<'a> {
+    fn next(&mut self) -> Option<&'a str> {
+        let rest = self.bytes.get(self.position..)?;
+        let end = rest.iter().position(|byte| *byte == b'\n')?;
+        self.position += end + 1;
+
+        std::str::from_utf8(&rest[..end]).ok()
+    }
+}
+
+/// Reads one scanline into `scanline`, returning the remaining data.
+fn read_scanline<'a>(
+    data: &'a [u8],
+    scanline: &mut [[u8; 4]],
+) -> Result<&'a [u8], HdrError> {
+    let columns = scanline.len();
+    let truncated = || HdrError::Truncated {
+        expected: columns * 4,
+        found: data.len(),
+    };
+
+    let encoded = (8..=0x7FFF).contains(&columns)
+        && data.len() >= 4
+        && data[0] == 2
+        && data[1] == 2
+        && ((data[2] as usize) << 8 | data[3] as usize) == columns;
+
+    if !encoded {
+        let bytes = data.get(..columns * 4).ok_or_else(truncated)?;
+        for (pixel, rgbe) in scanline.iter_mut().zip(bytes.chunks_exact(4)) {
+            *pixel = rgbe.try_into().unwrap();
+        }
+        return Ok(&data[columns * 4..]);
+    }
+
+    // Every channel is run length encoded separately.
+    let mut position = 4;
+    let mut values = Vec::with_capacity(columns);
+    for channel in 0..4 {
+        values.clear();
+
+        while values.len() < columns {
+            let count = *data.get(position).ok_or_else(truncated)? as usize;
+            position += 1;
+
+            let run = count > 128;
+            let length = if run { count - 128 } else { count };
+            if length == 0 || values.len() + length > columns {
+                return Err(HdrError::InvalidData(format!(
+                    "run of {} overflows the scanline",
+                    length
+                )));
+            }
+
+            if run {
+                let value = *data.get(position).ok_or_else(truncated)?;
+                values.resize(values.len() + length, value);
+                position += 1;
+            } else {
+                let literals = data
+                    .get(position..position + length)
+                    .ok_or_else(truncated)?;
+                values.extend_from_slice(literals);
+                position += length;
+            }
+        }
+
+        for (pixel, value) in scanline.iter_mut().zip(values.iter()) {
+            pixel[channel] = *value;
+        }
+    }
+
+    Ok(&data[position..])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn image() -> HdrImage {
+        let mut image = HdrImage::new(3, 2);
+        image.set(0, 0, Vector3::new(1.0, 0.5, 0.25));
+        image.set(1, 0, Vector3::new(100.0, 0.0, 3.0));
+        image.set(
+            2,
+            0,
+            Vector3::new(0.001, 0.002, 0.003),
+        );
+        image.set(
+            1,
+            1,
+            Vector3::new(65504.0, 1e-3, 42.0),
+        );
+        image
+    }
+
+    fn assert_close(a: &HdrImage, b: &HdrImage, tolerance: f64) {
+        assert_eq!(a.columns(), b.columns());
+        assert_eq!(a.rows(), b.rows());
+
+        for (a, b) in a.pixels.iter().zip(b.pixels.iter()) {
+            let brightest = a.x.max(a.y).max(a.z).max(1e-30);
+            for (a, b) in [(a.x, b.x), (a.y, b.y), (a.z, b.z)] {
+                assert!(
+                    (a - b).abs() <= tolerance * brightest,
+                    "{} != {}",
+                    a,
+                    b
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn rgbe_one() {
+        assert_eq!(
+            to_rgbe(Vector3::new(1.0, 1.0, 1.0)),
+            [128, 128, 128, 129]
+        );
+        assert_eq!(
+            from_rgbe([128, 128, 128, 129]),
+            Vector3::new(1.0, 1.0, 1.0)
+        );
+    }
+
+    #[test]
+    fn rgbe_saturates() {
+        for value in [f64::INFINITY, f64::MAX, f32::MAX as f64, 2f64.powi(127)]
+        {
+            assert_eq!(
+                to_rgbe(Vector3::new(value, value, value)),
+                [255, 255, 255, 255]
+            );
+        }
+        assert_eq!(
+            to_rgbe(Vector3::new(
+                f64::INFINITY,
+                1.0,
+                0.0
+            )),
+            [255, 0, 0, 255]
+        );
+    }
+
+    #[test]
+    fn rgbe_black() {
+        assert_eq!(
+            to_rgbe(Vector3::new(0.0, 0.0, 0.0)),
+            [0, 0, 0, 0]
+        );
+        assert_eq!(
+            from_rgbe([0, 0, 0, 0]),
+            Vector3::new(0.0, 0.0, 0.0)
+        );
+    }
+
+    #[test]
+    fn hdr_round_trip() {
+        let mut out = Vec::new();
+        image().write_hdr(&mut out).unwrap();
+
+        assert!(out.starts_with(b"#?RADIANCE\n"));
+        let read = HdrImage::read_hdr(&out[..]).unwrap();
+        assert_close(&image(), &read, 1.0 / 128.0);
+    }
+
+    #[test]
+    fn hdr_run_length_encoded() {
+        let mut file =
+            b"#?RADIANCE\n# comment\nFORMAT=32-bit_rle_rgbe\n\n-Y 1 +X 8\n"
+                .to_vec();
+        file.extend_from_slice(&[2, 2, 0, 8]);
+        // Red: a run of 8.
+        file.extend_from_slice(&[136, 128]);
+        // Green: 4 literals then a run of 4.
+        file.extend_from_slice(&[4, 0, 64, 128, 255, 132, 32]);
+        // Blue and exponent: runs of 8.
+        file.extend_from_slice(&[136, 0, 136, 129]);
+
+        let image = HdrImage::parse_hdr(&file).unwrap();
+        assert_eq!(
+            image.get(0, 0),
+            Some(&Vector3::new(1.0, 0.0, 0.0))
+        );
+        assert_eq!(
+            image.get(3, 0),
+            Some(&Vector3::new(
+                1.0,
+                255.0 / 128.0,
+                0.0
+            ))
+        );
+        assert_eq!(
+            image.get(7, 0),
+            Some(&Vector3::new(1.0, 0.25, 0.0))
+        );
+    }
+
+    #[test]
+    fn hdr_errors() {
+        assert!(matches!(
+            HdrImage::parse_hdr(b"P6\n"),
+            Err(HdrError::UnsupportedFormat(_))
+        ));
+        assert!(matches!(
+            HdrImage::parse_hdr(b"#?RADIANCE\nFORMAT=32-bit_rle_xyze\n\n"),
+            Err(HdrError::UnsupportedFormat(_))
+        ));
+        assert!(matches!(
+            HdrImage::parse_hdr(b"#?RADIANCE\n\n+Y 1 +X 1\n"),
+            Err(HdrError::UnsupportedFormat(_))
+        ));
+        assert!(matches!(
+            HdrImage::parse_hdr(b"#?RADIANCE\n\n-Y a +X 1\n"),
+            Err(HdrError::InvalidHeader(_))
+        ));
+        // The scanline buffer must not be sized from an unchecked width.
+        assert!(matches!(
+            HdrImage::parse_hdr(b"#?RADIANCE\n\n-Y 0 +X 1099511627776\n"),
+            Err(HdrError::InvalidHeader(_))
+        ));
+        assert!(matches!(
+            HdrImage::parse_hdr(b"#?RADIANCE\n\n-Y 1 +X 2\n\x80\x80\x80\x81"),
+            Err(HdrError::Truncated { .. })
+        ));
+    }
+
+    #[test]
+    fn hdr_run_overflow() {
+        let mut file = b"#?RADIANCE\n\n-Y 1 +X 8\n".to_vec();
+        file.extend_from_slice(&[2, 2, 0, 8, 137, 0]);
+
+        assert!(matches!(
+            HdrImage::parse_hdr(&file),
+            Err(HdrError::InvalidData(_))
+        ));
+    }
+
+    #[test]
+    fn pfm_round_trip() {
+        let mut out = Vec::new();
+        image().write_pfm(&mut out).unwrap();
+
+        assert!(out.starts_with(b"PF\n3 2\n-1.0\n"));
+        let read = HdrImage::read_pfm(&out[..]).unwrap();
+        assert_close(&image(), &read, 1e-6);
+    }
+
+    #[test]
+    fn pfm_bottom_up_big_endian_grayscale() {
+        let mut file = b"Pf\n1 2\n1.0\n".to_vec();
+        file.extend_from_slice(&2.0f32.to_be_bytes());
+        file.extend_from_slice(&0.5f32.to_be_bytes());
+
+        let image = HdrImage::parse_pfm(&file).unwrap();
+        assert_eq!(
+            image.get(0, 0),
+            Some(&Vector3::new(0.5, 0.5, 0.5))
+        );
+        assert_eq!(
+            image.get(0, 1),
+            Some(&Vector3::new(2.0, 2.0, 2.0))
+        );
+    }
+
+    #[test]
+    fn pfm_errors() {
+        assert!(matches!(
+            HdrImage::parse_pfm(b"P6\n1 1\n255\n"),
+            Err(HdrError::UnsupportedFormat(_))
+        ));
+        assert!(matches!(
+            HdrImage::parse_pfm(b"PF\n1 1\n0\n"),
+            Err(HdrError::InvalidHeader(_))
+        ));
+        assert!(matches!(
+            HdrImage::parse_pfm(b"PF\n1 1\n-1.0\n\0\0\0\0"),
+            Err(HdrError::Truncated {
+                expected: 12,
+                found: 4
+            })
+        ));
+    }
+}
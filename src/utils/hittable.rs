@@ -1,20 +1,30 @@
+use super::material::Material;
+use crate::math::aabb::Aabb;
 use crate::math::ray::Ray;
 use crate::math::vector3::Vector3;
+use std::any::Any;
 use std::ops::RangeInclusive;
 
 /// Describes where a hit happens and it's normal.
-pub struct Hit {
+pub struct Hit<'a> {
     pub position: Vector3,
     pub normal: Vector3,
     /// Interval for the `Ray` object.
     pub t: f64,
+    /// Whether the ray hit the outside of the surface.
+    pub front_face: bool,
+    /// Weights `(u, v)` of the second and third vertex of a triangle, the
+    /// first vertex weighs `1 - u - v`. `None` for other surfaces.
+    pub barycentric: Option<(f64, f64)>,
+    /// Material of the surface that was hit, borrowed from the object.
+    pub material: &'a dyn Material,
 }
 
-impl Hit {
-    /// Set the face normal to always point outwards.
+impl Hit<'_> {
+    /// Set the face normal to always point against the ray.
     pub fn set_face_normal(&mut self, ray: &Ray, outward_normal: Vector3) {
-        let front_face = ray.direction.dot(outward_normal) < 0.0;
-        self.normal = if front_face {
+        self.front_face = ray.direction.dot(outward_normal) < 0.0;
+        self.normal = if self.front_face {
             outward_normal
         } else {
             -outward_normal
@@ -23,6 +33,16 @@ impl Hit {
 }
 
 /// Returns a `Hit` object if a certain ray hits it.
-pub trait Hittable {
-    fn hit(&self, ray: &Ray, valid_range: RangeInclusive<f64>) -> Option<Hit>;
+///
+/// Objects are shared between render threads, so they must be `Send` and
+/// `Sync`. `Any` lets the scene formats find the concrete type to write.
+pub trait Hittable: Any + Send + Sync {
+    fn hit(
+        &self,
+        ray: &Ray,
+        valid_range: RangeInclusive<f64>,
+    ) -> Option<Hit<'_>>;
+
+    /// Returns the box enclosing the object, or `None` if it is unbounded.
+    fn bounding_box(&self) -> Option<Aabb>;
 }
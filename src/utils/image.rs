@@ -0,0 +1,123 @@
+use super::color::Transfer;
+use super::ppm::RGBTriplet;
+use super::ppm::PPM;
+use crate::math::vector3::Vector3;
+
+/// High dynamic range image holding linear radiance for every pixel.
+#[derive(Clone, PartialEq)]
+pub struct HdrImage {
+    columns: usize,
+    rows: usize,
+    pub pixels: Vec<Vector3>,
+}
+
+impl HdrImage {
+    /// Creates a black image with the specified `columns` and `rows`.
+    pub fn new(columns: u64, rows: u64) -> Self {
+        let columns = columns as usize;
+        let rows = rows as usize;
+
+        Self {
+            columns,
+            rows,
+            pixels: vec![Vector3::new(0.0, 0.0, 0.0); columns * rows],
+        }
+    }
+
+    pub fn columns(&self) -> usize {
+        self.columns
+    }
+
+    pub fn rows(&self) -> usize {
+        self.rows
+    }
+
+    /// Sets the radiance of the specified pixel.
+    pub fn set(&mut self, column: u64, row: u64, new: Vector3) -> Vector3 {
+        let column = column as usize;
+        let row = row as usize;
+
+        self.pixels[self.columns * row + column] = new;
+        new
+    }
+
+    /// Gets the radiance at the specified `column` and `row`.
+    pub fn get(&self, column: usize, row: usize) -> Option<&Vector3> {
+        self.pixels.get(self.columns * row + column)
+    }
+
+    /// Encodes the image with `transfer` and quantizes it to 8 bits,
+    /// clamping radiance to `[0, 1]`.
+    pub fn to_ppm(&self, transfer: Transfer) -> PPM {
+        let mut ppm = PPM::new(
+            self.columns as u64,
+            self.rows as u64,
+        );
+
+        for (pixel, radiance) in ppm.pixels.iter_mut().zip(self.pixels.iter())
+        {
+            *pixel = RGBTriplet::from_radiance(*radiance, transfer);
+        }
+
+        ppm
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn set_and_get() {
+        let mut image = HdrImage::new(3, 2);
+        image.set(2, 1, Vector3::new(4.0, 5.0, 6.0));
+
+        assert_eq!(
+            image.get(2, 1),
+            Some(&Vector3::new(4.0, 5.0, 6.0))
+        );
+        assert_eq!(
+            image.get(0, 0),
+            Some(&Vector3::new(0.0, 0.0, 0.0))
+        );
+        assert_eq!(image.get(0, 2), None);
+    }
+
+    #[test]
+    fn to_ppm_clamps() {
+        let mut image = HdrImage::new(3, 1);
+        image.set(0, 0, Vector3::new(0.5, 1.0, 0.0));
+        image.set(1, 0, Vector3::new(7.5, 1.5, 2.0));
+        image.set(
+            2,
+            0,
+            Vector3::new(-1.0, 0.25, f64::NAN),
+        );
+
+        let ppm = image.to_ppm(Transfer::Linear);
+        assert_eq!(
+            ppm.get(0, 0).unwrap().to_string(),
+            "127 255 0"
+        );
+        assert_eq!(
+            ppm.get(1, 0).unwrap().to_string(),
+            "255 255 255"
+        );
+        assert_eq!(
+            ppm.get(2, 0).unwrap().to_string(),
+            "0 63 0"
+        );
+    }
+
+    #[test]
+    fn to_ppm_srgb() {
+        let mut image = HdrImage::new(1, 1);
+        image.set(0, 0, Vector3::new(0.0, 0.18, 1.0));
+
+        let ppm = image.to_ppm(Transfer::Srgb);
+        assert_eq!(
+            ppm.get(0, 0).unwrap().to_string(),
+            "0 118 255"
+        );
+    }
+}
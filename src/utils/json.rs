@@ -0,0 +1,821 @@
+use crate::math::vector3::Vector3;
+
+/// A JSON value, object members keep the order they were written in.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Json {
+    Null,
+    Bool(bool),
+    Number(f64),
+    String(String),
+    Array(Vec<Json>),
+    Object(Vec<(String, Json)>),
+}
+
+/// Error returned when a document is not valid JSON, at the 1 based `line`
+/// and `column`.
+#[derive(Debug, PartialEq)]
+pub struct JsonError {
+    pub line: usize,
+    pub column: usize,
+    pub message: String,
+}
+
+impl std::fmt::Display for JsonError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(
+            f,
+            "{}:{}: {}",
+            self.line, self.column, self.message
+        )
+    }
+}
+
+impl std::error::Error for JsonError {}
+
+impl From<Vector3> for Json {
+    fn from(vector: Vector3) -> Self {
+        Json::Array(vec![
+            Json::Number(vector.x),
+            Json::Number(vector.y),
+            Json::Number(vector.z),
+        ])
+    }
+}
+
+impl From<f64> for Json {
+    fn from(number: f64) -> Self {
+        Json::Number(number)
+    }
+}
+
+impl From<&str> for Json {
+    fn from(string: &str) -> Self {
+        Json::String(string.to_string())
+    }
+}
+
+impl Json {
+    /// Creates an object from `(key, value)` pairs.
+    pub fn object<'a>(
+        members: impl IntoIterator<Item = (&'a str, Json)>,
+    ) -> Self {
+        Json::Object(
+            members
+                .into_iter()
+                .map(|(key, value)| (key.to_string(), value))
+                .collect(),
+        )
+    }
+
+    /// Parses a complete document, surrounding whitespace is allowed.
+    pub fn parse(source: &str) -> Result<Self, JsonError> {
+        let mut parser = Parser {
+            source: source.as_bytes(),
+            position: 0,
+            depth: 0,
+        };
+
+        parser.skip_whitespace();
+        let value = parser.value()?;
+        parser.skip_whitespace();
+        if parser.position < source.len() {
+            return Err(parser.error("unexpected data after the value"));
+        }
+
+        Ok(value)
+    }
+
+    /// Returns the member called `key` if this is an object.
+    pub fn get(&self, key: &str) -> Option<&Json> {
+        match self {
+            Json::Object(members) => members
+                .iter()
+                .find(|(name, _)| name == key)
+                .map(|(_, value)| value),
+            _ => None,
+        }
+    }
+
+    pub fn as_f64(&self) -> Option<f64> {
+        match self {
+            Json::Number(number) => Some(*number),
+            _ => None,
+        }
+    }
+
+    /// Returns the number if it is a non negative integer that fits.
+    pub fn as_u64(&self) -> Option<u64> {
+        // `u64::MAX as f64` rounds up to 2^64, which doesn't fit.
+        self.as_f64()
+            .filter(|number| {
+                number.fract() == 0.0
+                    && *number >= 0.0
+                    && *number < u64::MAX as f64
+            })
+            .map(|number| number as u64)
+    }
+
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            Json::String(string) => Some(string),
+            _ => None,
+        }
+    }
+
+    pub fn as_array(&self) -> Option<&[Json]> {
+        match self {
+            Json::Array(values) => Some(values),
+            _ => None,
+        }
+    }
+
+    /// Returns the vector if this is an array of three finite numbers.
+    pub fn as_vector3(&self) -> Option<Vector3> {
+        self.as_any_vector3().filter(|vector| {
+            vector.x.is_finite()
+                && vector.y.is_finite()
+                && vector.z.is_finite()
+        })
+    }
+
+    /// Returns the vector if this is an array of three numbers, finite or
+    /// not.
+    fn as_any_vector3(&self) -> Option<Vector3> {
+        match self.as_array()? {
+            [x, y, z] => Some(Vector3::new(
+                x.as_f64()?,
+                y.as_f64()?,
+                z.as_f64()?,
+            )),
+            _ => None,
+        }
+    }
+
+    /// Returns the member called `key`, or an error naming it.
+    pub fn field(&self, key: &str) -> Result<&Json, String> {
+        self.get(key).ok_or_else(|| format!("missing `{}`", key))
+    }
+
+    /// Numbers too large for an `f64`, such as `1e999`, are rejected.
+    pub fn number_field(&self, key: &str) -> Result<f64, String> {
+        let number = self
+            .field(key)?
+            .as_f64()
+            .ok_or_else(|| format!("`{}` must be a number", key))?;
+        if !number.is_finite() {
+            return Err(format!("`{}` must be finite", key));
+        }
+
+        Ok(number)
+    }
+
+    pub fn str_field(&self, key: &str) -> Result<&str, String> {
+        self.field(key)?
+            .as_str()
+            .ok_or_else(|| format!("`{}` must be a string", key))
+    }
+
+    pub fn vector3_field(&self, key: &str) -> Result<Vector3, String> {
+        let field = self.field(key)?;
+        field
+            .as_vector3()
+            .ok_or_else(|| match field.as_any_vector3() {
+                Some(_) => format!("`{}` must be finite", key),
+                None => format!(
+                    "`{}` must be an array of three numbers",
+                    key
+                ),
+            })
+    }
+
+    /// Writes the value with two space indentation, arrays holding only
+    /// numbers stay on a single line.
+    fn write_pretty(
+        &self,
+        f: &mut std::fmt::Formatter,
+        indent: usize,
+    ) -> std::fmt::Result {
+        let pad = |f: &mut std::fmt::Formatter, indent: usize| {
+            write!(f, "{:1$}", "", indent * 2)
+        };
+
+        match self {
+            Json::Array(values)
+                if values.is_empty()
+                    || values
+                        .iter()
+                        .all(|value| matches!(value, Json::Number(_))) =>
+            {
+                write!(f, "[")?;
+                for (i, value) in values.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ", ")?;
+                    }
+                    value.write_pretty(f, indent)?;
+                }
+                write!(f, "]")
+            }
+            Json::Array(values) => {
+                writeln!(f, "[")?;
+                for (i, value) in values.iter().enumerate() {
+                    pad(f, indent + 1)?;
+                    value.write_pretty(f, indent + 1)?;
+                    writeln!(
+                        f,
+                        "{}",
+                        if i + 1 < values.len() { "," } else { "" }
+                    )?;
+                }
+                pad(f, indent)?;
+                write!(f, "]")
+            }
+            Json::Object(members) if members.is_empty() => write!(f, "{{}}"),
+            Json::Object(members) => {
+                writeln!(f, "{{")?;
+                for (i, (key, value)) in members.iter().enumerate() {
+                    pad(f, indent + 1)?;
+                    write_string(f, key)?;
+                    write!(f, ": ")?;
+                    value.write_pretty(f, indent + 1)?;
+                    writeln!(
+                        f,
+                        "{}",
+                        if i + 1 < members.len() { "," } else { "" }
+                    )?;
+                }
+                pad(f, indent)?;
+                write!(f, "}}")
+            }
+            Json::Null => write!(f, "null"),
+            Json::Bool(value) => write!(f, "{}", value),
+            // JSON has no infinities or NaN.
+            Json::Number(number) if !number.is_finite() => write!(f, "null"),
+            Json::Number(number) => write!(f, "{}", number),
+            Json::String(string) => write_string(f, string),
+        }
+    }
+}
+
+impl std::fmt::Display for Json {
+    /// Pretty prints the value, numbers are written with the shortest
+    /// representation that reads back to the same `f64`.
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        self.write_pretty(f, 0)
+    }
+}
+
+fn write_string(
+    f: &mut std::fmt::Formatter,
+    string: &str,
+) -> std::fmt::Result {
+    write!(f, "\"")?;
+    for character in string.chars() {
+        match character {
+            '"' => write!(f, "\\\"")?,
+            '\\' => write!(f, "\\\\")?,
+            '\n' => write!(f, "\\n")?,
+            '\r' => write!(f, "\\r")?,
+            '\t' => write!(f, "\\t")?,
+            c if (c as u32) < 0x20 => write!(f, "\\u{:04x}", c as u32)?,
+            c => write!(f, "{}", c)?,
+        }
+    }
+    write!(f, "\"")
+}
+
+/// Deepest nesting accepted, so hostile input can't overflow the stack.
+const MAX_DEPTH: usize = 128;
+
+struct Parser<'a> {
+    source: &'a [u8],
+    position: usize,
+    depth: usize,
+}
+
+impl Parser<'_> {
+    /// Builds an error at the current position, counting columns in
+    /// characters.
+    fn error(&self, message: impl Into<String>) -> JsonError {
+        let before = &self.source[..self.position.min(self.source.len())];
+        let line_start = before
+            .iter()
+            .rposition(|byte| *byte == b'\n')
+            .map_or(0, |newline| newline + 1);
+        let column = String::from_utf8_lossy(&before[line_start..])
+            .chars()
+            .count();
+
+        JsonError {
+            line: before.iter().filter(|byte| **byte == b'\n').count() + 1,
+            column: column + 1,
+            message: message.into(),
+        }
+    }
+
+    fn peek(&self) -> Option<u8> {
+        self.source.get(self.position).copied()
+    }
+
+    fn skip_whitespace(&mut self) {
+        while let Some(b' ' | b'\t' | b'\n' | b'\r') = self.peek() {
+            self.position += 1;
+        }
+    }
+
+    fn expect(&mut self, byte: u8) -> Result<(), JsonError> {
+        if self.peek() == Some(byte) {
+            self.position += 1;
+            Ok(())
+        } else {
+            Err(self.unexpected(&format!("`{}`", byte as char)))
+        }
+    }
+
+    fn unexpected(&self, expected: &str) -> JsonError {
+        match self.peek() {
+            Some(_) => {
+                let rest =
+                    String::from_utf8_lossy(&self.source[self.position..]);
+                let found = rest.chars().next().unwrap_or('?');
+                self.error(format!(
+                    "expected {}, found `{}`",
+                    expected, found
+                ))
+            }
+            None => self.error(format!(
+                "expected {}, found the end",
+                expected
+            )),
+        }
+    }
+
+    fn value(&mut self) -> Result<Json, JsonError> {
+        match self.peek() {
+            Some(b'{') => self.nested(Self::object),
+            Some(b'[') => self.nested(Self::array),
+            Some(b'"') => Ok(Json::String(self.string()?)),
+            Some(b'-' | b'0'..=b'9') => self.number(),
+            Some(b't') => self.literal("true", Json::Bool(true)),
+            Some(b'f') => self.literal("false", Json::Bool(false)),
+            Some(b'n') => self.literal("null", Json::Null),
+            _ => Err(self.unexpected("a value")),
+        }
+    }
+
+    fn nested(
+        &mut self,
+        parse: fn(&mut Self) -> Result<Json, JsonError>,
+    ) -> Result<Json, JsonError> {
+        if self.depth == MAX_DEPTH {
+            return Err(self.error("too deeply nested"));
+        }
+
+        self.depth += 1;
+        let value = parse(self);
+        self.depth -= 1;
+        value
+    }
+
+    fn literal(&mut self, word: &str, value: Json) -> Result<Json, JsonError> {
+        if self.source[self.position..].starts_with(word.as_bytes()) {
+            self.position += word.len();
+            Ok(value)
+        } else {
+            Err(self.error("invalid literal"))
+        }
+    }
+
+    fn object(&mut self) -> Result<Json, JsonError> {
+        self.expect(b'{')?;
+        self.skip_whitespace();
+
+        let mut members = Vec::new();
+        if self.peek() == Some(b'}') {
+            self.position += 1;
+            return Ok(Json::Object(members));
+        }
+
+        loop {
+            self.skip_whitespace();
+            if self.peek() != Some(b'"') {
+                return Err(self.unexpected("a string key"));
+            }
+            let key = self.string()?;
+            self.skip_whitespace();
+            self.expect(b':')?;
+            self.skip_whitespace();
+            members.push((key, self.value()?));
+            self.skip_whitespace();
+
+            match self.peek() {
+                Some(b',') => self.position += 1,
+                Some(b'}') => {
+                    self.position += 1;
+                    return Ok(Json::Object(members));
+                }
+                _ => return Err(self.unexpected("`,` or `}`")),
+            }
+        }
+    }
+
+    fn array(&mut self) -> Result<Json, JsonError> {
+        self.expect(b'[')?;
+        self.skip_whitespace();
+
+        let mut values = Vec::new();
+        if self.peek() == Some(b']') {
+            self.position += 1;
+            return Ok(Json::Array(values));
+        }
+
+        loop {
+            self.skip_whitespace();
+            values.push(self.value()?);
+            self.skip_whitespace();
+
+            match self.peek() {
+                Some(b',') => self.position += 1,
+                Some(b']') => {
+                    self.position += 1;
+                    return Ok(Json::Array(values));
+                }
+                _ => return Err(self.unexpected("`,` or `]`")),
+            }
+        }
+    }
+
+    fn number(&mut self) -> Result<Json, JsonError> {
+        let start = self.position;
+        let digits = |parser: &mut Self| {
+            let start = parser.position;
+            while let Some(b'0'..=b'9') = parser.peek() {
+                parser.position += 1;
+            }
+            parser.position > start
+        };
+
+        if self.peek() == Some(b'-') {
+            self.position += 1;
+        }
+        if self.peek() == Some(b'0') {
+            self.position += 1;
+        } else if !digits(self) {
+            return Err(self.unexpected("a digit"));
+        }
+        if self.peek() == Some(b'.') {
+            self.position += 1;
+            if !digits(self) {
+                return Err(self.unexpected("a digit"));
+            }
+        }
+        if let Some(b'e' | b'E') = self.peek() {
+            self.position += 1;
+            if let Some(b'+' | b'-') = self.peek() {
+                self.position += 1;
+            }
+            if !digits(self) {
+                return Err(self.unexpected("a digit"));
+            }
+        }
+
+        // The grammar above only accepts ASCII that Rust can parse.
+        let text = std::str::from_utf8(&self.source[start..self.position])
+            .expect("numbers are ASCII");
+        Ok(Json::Number(text.parse().expect(
+            "numbers follow the grammar",
+        )))
+    }
+
+    fn string(&mut self) -> Result<String, JsonError> {
+        self.expect(b'"')?;
+
+        let mut bytes = Vec::new();
+        loop {
+            match self.peek() {
+                None => return Err(self.error("unterminated string")),
+                Some(b'"') => {
+                    self.position += 1;
+                    break;
+                }
+                Some(b'\\') => {
+                    self.position += 1;
+                    let escaped = match self.peek() {
+                        Some(b'u') => {
+                            self.position += 1;
+                            self.unicode_escape()?
+                        }
+                        Some(byte) => {
+                            let escaped = match byte {
+                                b'"' => '"',
+                                b'\\' => '\\',
+                                b'/' => '/',
+                                b'b' => '\u{8}',
+                                b'f' => '\u{c}',
+                                b'n' => '\n',
+                                b'r' => '\r',
+                                b't' => '\t',
+                                _ => return Err(self.error("invalid escape")),
+                            };
+                            self.position += 1;
+                            escaped
+                        }
+                        None => return Err(self.error("unterminated string")),
+                    };
+
+                    let mut buffer = [0; 4];
+                    bytes.extend_from_slice(
+                        escaped.encode_utf8(&mut buffer).as_bytes(),
+                    );
+                }
+                Some(byte) if byte < 0x20 => {
+                    return Err(self.error("control character in string"))
+                }
+                Some(byte) => {
+                    bytes.push(byte);
+                    self.position += 1;
+                }
+            }
+        }
+
+        String::from_utf8(bytes)
+            .map_err(|_| self.error("string is not valid UTF-8"))
+    }
+
+    /// Parses the digits of a `\\u` escape, combining surrogate pairs.
+    fn unicode_escape(&mut self) -> Result<char, JsonError> {
+        let first = self.hex4()?;
+
+        let code = if (0xD800..0xDC00).contains(&first) {
+            if !self.source[self.position..].starts_with(b"\\u") {
+                return Err(self.error("unpaired surrogate"));
+            }
+            self.position += 2;
+
+            let second = self.hex4()?;
+            if !(0xDC00..0xE000).contains(&second) {
+                return Err(self.error("unpaired surrogate"));
+            }
+            0x10000 + ((first - 0xD800) << 10) + (second - 0xDC00)
+        } else {
+            first
+        };
+
+        char::from_u32(code).ok_or_else(|| self.error("unpaired surrogate"))
+    }
+
+    fn hex4(&mut self) -> Result<u32, JsonError> {
+        let digits = self
+            .source
+            .get(self.position..self.position + 4)
+            .and_then(|digits| std::str::from_utf8(digits).ok())
+            .filter(|digits| {
+                digits.bytes().all(|byte| byte.is_ascii_hexdigit())
+            })
+            .ok_or_else(|| self.error("expected four hex digits"))?;
+
+        self.position += 4;
+        Ok(u32::from_str_radix(digits, 16).expect("digits are hex"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn error(source: &str) -> (usize, usize, String) {
+        let error = Json::parse(source).unwrap_err();
+        (
+            error.line,
+            error.column,
+            error.message,
+        )
+    }
+
+    #[test]
+    fn parse_values() {
+        assert_eq!(
+            Json::parse(
+                r#" {"a": [1, -2.5e2, 0.125], "b": {"c": null},
+                "d": true, "e": false, "f": "x\"\\\/\n\u00e9\ud83d\ude00"} "#
+            ),
+            Ok(Json::object([
+                (
+                    "a",
+                    Json::Array(vec![
+                        Json::Number(1.0),
+                        Json::Number(-250.0),
+                        Json::Number(0.125),
+                    ])
+                ),
+                (
+                    "b",
+                    Json::object([("c", Json::Null)])
+                ),
+                ("d", Json::Bool(true)),
+                ("e", Json::Bool(false)),
+                ("f", Json::from("x\"\\/\né😀")),
+            ]))
+        );
+    }
+
+    #[test]
+    fn empty_containers() {
+        assert_eq!(
+            Json::parse("[ ]"),
+            Ok(Json::Array(Vec::new()))
+        );
+        assert_eq!(
+            Json::parse("{}"),
+            Ok(Json::Object(Vec::new()))
+        );
+    }
+
+    #[test]
+    fn accessors() {
+        let json =
+            Json::parse(r#"{"v": [1, 2, 3], "n": 4, "s": "x"}"#).unwrap();
+
+        assert_eq!(
+            json.get("v").and_then(Json::as_vector3),
+            Some(Vector3::new(1.0, 2.0, 3.0))
+        );
+        assert_eq!(
+            json.get("n").and_then(Json::as_u64),
+            Some(4)
+        );
+        assert_eq!(
+            json.get("s").and_then(Json::as_str),
+            Some("x")
+        );
+        assert_eq!(json.get("missing"), None);
+        assert_eq!(
+            json.get("s").and_then(Json::as_f64),
+            None
+        );
+        assert_eq!(Json::Number(1.5).as_u64(), None);
+        assert_eq!(Json::Number(-1.0).as_u64(), None);
+        assert_eq!(
+            Json::Number(2f64.powi(63)).as_u64(),
+            Some(1 << 63)
+        );
+        assert_eq!(
+            Json::Number(2f64.powi(64)).as_u64(),
+            None
+        );
+    }
+
+    #[test]
+    fn field_errors() {
+        let json = Json::parse(r#"{"n": "x", "v": [1, 2]}"#).unwrap();
+
+        assert_eq!(
+            json.number_field("missing"),
+            Err("missing `missing`".to_string())
+        );
+        assert_eq!(
+            json.number_field("n"),
+            Err("`n` must be a number".to_string())
+        );
+        assert_eq!(json.str_field("n"), Ok("x"));
+        assert_eq!(
+            json.vector3_field("v"),
+            Err("`v` must be an array of three numbers".to_string())
+        );
+
+        let json =
+            Json::parse(r#"{"n": 1e999, "v": [-1e999, 0, 0]}"#).unwrap();
+        assert_eq!(
+            json.number_field("n"),
+            Err("`n` must be finite".to_string())
+        );
+        assert_eq!(
+            json.vector3_field("v"),
+            Err("`v` must be finite".to_string())
+        );
+        assert_eq!(
+            json.get("v").and_then(Json::as_vector3),
+            None
+        );
+    }
+
+    #[test]
+    fn write_round_trip() {
+        let json = Json::object([
+            (
+                "numbers",
+                Json::from(Vector3::new(0.1, -2.0, 1e-7)),
+            ),
+            ("empty", Json::Array(Vec::new())),
+            (
+                "nested",
+                Json::Array(vec![Json::object([]), Json::Null]),
+            ),
+            (
+                "text",
+                Json::from("tab\t\"quote\" \u{1}"),
+            ),
+        ]);
+
+        let text = json.to_string();
+        assert_eq!(Json::parse(&text), Ok(json));
+        assert!(text.contains("\"numbers\": [0.1, -2, 0.0000001]"));
+    }
+
+    #[test]
+    fn non_finite_numbers_are_null() {
+        assert_eq!(
+            Json::Number(f64::NAN).to_string(),
+            "null"
+        );
+    }
+
+    #[test]
+    fn errors() {
+        assert_eq!(
+            error("{\n  \"a\": [1, 2,]\n}"),
+            (
+                2,
+                14,
+                "expected a value, found `]`".to_string()
+            )
+        );
+        assert_eq!(
+            error("[1 2]"),
+            (
+                1,
+                4,
+                "expected `,` or `]`, found `2`".to_string()
+            )
+        );
+        assert_eq!(
+            error("{\"a\" 1}"),
+            (
+                1,
+                6,
+                "expected `:`, found `1`".to_string()
+            )
+        );
+        assert_eq!(
+            error("[\"é\", x]"),
+            (
+                1,
+                7,
+                "expected a value, found `x`".to_string()
+            )
+        );
+        assert_eq!(
+            error("[1"),
+            (
+                1,
+                3,
+                "expected `,` or `]`, found the end".to_string()
+            )
+        );
+        assert_eq!(
+            error("1 2").2,
+            "unexpected data after the value"
+        );
+        assert_eq!(
+            error("01").2,
+            "unexpected data after the value"
+        );
+        assert_eq!(
+            error("1.").2,
+            "expected a digit, found the end"
+        );
+        assert_eq!(error("tru").2, "invalid literal");
+        assert_eq!(
+            error("\"abc").2,
+            "unterminated string"
+        );
+        assert_eq!(
+            error("\"\\x\"").2,
+            "invalid escape"
+        );
+        assert_eq!(
+            error("\"\\ud800\"").2,
+            "unpaired surrogate"
+        );
+        assert_eq!(
+            error("\"\\u12\"").2,
+            "expected four hex digits"
+        );
+        assert_eq!(
+            error("\"a\nb\"").2,
+            "control character in string"
+        );
+    }
+
+    #[test]
+    fn nesting_is_limited() {
+        let deep = "[".repeat(MAX_DEPTH + 1);
+        assert_eq!(error(&deep).2, "too deeply nested");
+
+        let fine = format!(
+            "{}{}",
+            "[".repeat(MAX_DEPTH),
+            "]".repeat(MAX_DEPTH)
+        );
+        assert!(Json::parse(&fine).is_ok());
+    }
+}
@@ -0,0 +1,367 @@
+use super::hittable::Hit;
+use crate::math::random::Rng;
+use crate::math::ray::Ray;
+use crate::math::vector3::Vector3;
+use std::any::Any;
+
+/// Describes how a surface responds to an incoming ray.
+///
+/// `Any` lets the scene formats find the concrete type to write.
+pub trait Material: Any + Send + Sync {
+    /// Returns the attenuation and the scattered `Ray`, or `None` if the
+    /// ray was absorbed.
+    fn scatter(
+        &self,
+        ray: &Ray,
+        hit: &Hit,
+        rng: &mut Rng,
+    ) -> Option<(Vector3, Ray)>;
+
+    /// Returns the radiance emitted from the hit, black by default.
+    fn emitted(&self, _hit: &Hit) -> Vector3 {
+        Vector3::new(0.0, 0.0, 0.0)
+    }
+}
+
+/// Debug material that shades a surface by its normal.
+pub struct Normal;
+
+impl Material for Normal {
+    /// Maps the normal into the unit cube and scatters along it.
+    fn scatter(
+        &self,
+        _ray: &Ray,
+        hit: &Hit,
+        _rng: &mut Rng,
+    ) -> Option<(Vector3, Ray)> {
+        let attenuation = 0.5 * (hit.normal + Vector3::new(1.0, 1.0, 1.0));
+        Some((
+            attenuation,
+            Ray::new(hit.position, hit.normal),
+        ))
+    }
+}
+
+/// Ideal diffuse material.
+pub struct Lambertian {
+    pub albedo: Vector3,
+}
+
+impl Lambertian {
+    pub fn new(albedo: Vector3) -> Self {
+        Self { albedo }
+    }
+}
+
+impl Material for Lambertian {
+    /// Scatters in a cosine weighted hemisphere around the normal.
+    fn scatter(
+        &self,
+        _ray: &Ray,
+        hit: &Hit,
+        rng: &mut Rng,
+    ) -> Option<(Vector3, Ray)> {
+        let mut direction = hit.normal + rng.unit_vector();
+
+        // Catch a random vector that cancels out the normal.
+        if direction.near_zero() {
+            direction = hit.normal;
+        }
+
+        Some((
+            self.albedo,
+            Ray::new(hit.position, direction),
+        ))
+    }
+}
+
+/// Reflective material with an optional fuzzy reflection.
+pub struct Metal {
+    pub albedo: Vector3,
+    /// Radius of the sphere the reflected direction is perturbed by,
+    /// clamped to `[0, 1]`.
+    pub fuzz: f64,
+}
+
+impl Metal {
+    pub fn new(albedo: Vector3, fuzz: f64) -> Self {
+        Self {
+            albedo,
+            fuzz: fuzz.clamp(0.0, 1.0),
+        }
+    }
+}
+
+impl Material for Metal {
+    /// Mirrors the ray about the normal and absorbs it if the fuzz pushed
+    /// it below the surface.
+    fn scatter(
+        &self,
+        ray: &Ray,
+        hit: &Hit,
+        rng: &mut Rng,
+    ) -> Option<(Vector3, Ray)> {
+        let mut incoming = ray.direction;
+        let reflected = incoming.unit().reflect(hit.normal);
+        let direction = reflected + self.fuzz * rng.in_unit_sphere();
+
+        if direction.dot(hit.normal) > 0.0 {
+            Some((
+                self.albedo,
+                Ray::new(hit.position, direction),
+            ))
+        } else {
+            None
+        }
+    }
+}
+
+/// Clear material such as glass or water that refracts rays.
+pub struct Dielectric {
+    /// Index of refraction.
+    pub ior: f64,
+}
+
+impl Dielectric {
+    pub fn new(ior: f64) -> Self {
+        Self { ior }
+    }
+
+    /// Schlick's approximation of the Fresnel reflectance.
+    fn reflectance(cosine: f64, eta_ratio: f64) -> f64 {
+        let r0 = ((1.0 - eta_ratio) / (1.0 + eta_ratio)).powi(2);
+        r0 + (1.0 - r0) * (1.0 - cosine).powi(5)
+    }
+}
+
+impl Material for Dielectric {
+    /// Refracts the ray, or reflects it on total internal reflection and
+    /// with a probability given by the Fresnel reflectance.
+    fn scatter(
+        &self,
+        ray: &Ray,
+        hit: &Hit,
+        rng: &mut Rng,
+    ) -> Option<(Vector3, Ray)> {
+        let eta_ratio = if hit.front_face {
+            1.0 / self.ior
+        } else {
+            self.ior
+        };
+
+        let mut incoming = ray.direction;
+        let unit_direction = incoming.unit();
+        let cos_theta = (-unit_direction).dot(hit.normal).min(1.0);
+        let sin_theta = (1.0 - cos_theta * cos_theta).sqrt();
+
+        let cannot_refract = eta_ratio * sin_theta > 1.0;
+        let direction = if cannot_refract
+            || Self::reflectance(cos_theta, eta_ratio) > rng.next_f64()
+        {
+            unit_direction.reflect(hit.normal)
+        } else {
+            unit_direction.refract(hit.normal, eta_ratio)
+        };
+
+        Some((
+            Vector3::new(1.0, 1.0, 1.0),
+            Ray::new(hit.position, direction),
+        ))
+    }
+}
+
+/// Emissive material used for area lights.
+pub struct DiffuseLight {
+    pub emit: Vector3,
+}
+
+impl DiffuseLight {
+    pub fn new(emit: Vector3) -> Self {
+        Self { emit }
+    }
+}
+
+impl Material for DiffuseLight {
+    /// Lights absorb every incoming ray.
+    fn scatter(
+        &self,
+        _ray: &Ray,
+        _hit: &Hit,
+        _rng: &mut Rng,
+    ) -> Option<(Vector3, Ray)> {
+        None
+    }
+
+    /// Emits only from the outside of the surface.
+    fn emitted(&self, hit: &Hit) -> Vector3 {
+        if hit.front_face {
+            self.emit
+        } else {
+            Vector3::new(0.0, 0.0, 0.0)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn hit() -> Hit<'static> {
+        Hit {
+            position: Vector3::new(0.0, 0.0, -1.0),
+            normal: Vector3::new(0.0, 0.0, 1.0),
+            t: 1.0,
+            front_face: true,
+            barycentric: None,
+            material: &Normal,
+        }
+    }
+
+    fn ray() -> Ray {
+        Ray::new(
+            Vector3::new(0.0, 0.0, 0.0),
+            Vector3::new(0.0, 0.0, -1.0),
+        )
+    }
+
+    #[test]
+    fn dielectric_total_internal_reflection() {
+        let mut hit = hit();
+        hit.front_face = false;
+        let material = Dielectric::new(1.5);
+        let grazing = Ray::new(
+            Vector3::new(-1.0, 0.0, 0.0),
+            Vector3::new(1.0, 0.0, -0.2),
+        );
+        let mut rng = Rng::default();
+
+        for _ in 0..100 {
+            let (attenuation, scattered) =
+                material.scatter(&grazing, &hit, &mut rng).unwrap();
+
+            assert_eq!(
+                attenuation,
+                Vector3::new(1.0, 1.0, 1.0)
+            );
+            assert!(scattered.direction.dot(hit.normal) > 0.0);
+        }
+    }
+
+    #[test]
+    fn dielectric_refracts_head_on() {
+        let hit = hit();
+        let material = Dielectric::new(1.5);
+        let mut rng = Rng::default();
+
+        let refracted = (0..1000)
+            .filter_map(|_| material.scatter(&ray(), &hit, &mut rng))
+            .filter(|(_, scattered)| scattered.direction.z < 0.0)
+            .count();
+
+        // Schlick gives 4% reflectance at normal incidence.
+        assert!(refracted > 900);
+    }
+
+    #[test]
+    fn schlick_reflectance_at_grazing_angle() {
+        assert_eq!(
+            Dielectric::reflectance(0.0, 1.5),
+            1.0
+        );
+    }
+
+    #[test]
+    fn diffuse_light_emits_from_front() {
+        let mut hit = hit();
+        let material = DiffuseLight::new(Vector3::new(4.0, 4.0, 4.0));
+
+        assert!(material
+            .scatter(&ray(), &hit, &mut Rng::default())
+            .is_none());
+        assert_eq!(
+            material.emitted(&hit),
+            material.emit
+        );
+
+        hit.front_face = false;
+        assert_eq!(
+            material.emitted(&hit),
+            Vector3::new(0.0, 0.0, 0.0)
+        );
+    }
+
+    #[test]
+    fn default_emission_is_black() {
+        assert_eq!(
+            Normal.emitted(&hit()),
+            Vector3::new(0.0, 0.0, 0.0)
+        );
+    }
+
+    #[test]
+    fn metal_mirrors_without_fuzz() {
+        let hit = hit();
+        let material = Metal::new(Vector3::new(0.8, 0.8, 0.8), 0.0);
+        let ray = Ray::new(
+            Vector3::new(-1.0, 0.0, 0.0),
+            Vector3::new(1.0, 0.0, -1.0),
+        );
+
+        let (_, scattered) =
+            material.scatter(&ray, &hit, &mut Rng::default()).unwrap();
+        let mut expected = Vector3::new(1.0, 0.0, 1.0);
+
+        assert!((scattered.direction - expected.unit()).near_zero());
+    }
+
+    #[test]
+    fn metal_absorbs_below_surface() {
+        let hit = hit();
+        let material = Metal::new(Vector3::new(0.8, 0.8, 0.8), 1.0);
+        let grazing = Ray::new(
+            Vector3::new(-1.0, 0.0, 0.0),
+            Vector3::new(1.0, 0.0, -1e-6),
+        );
+        let mut rng = Rng::default();
+
+        let absorbed = (0..1000)
+            .filter(|_| material.scatter(&grazing, &hit, &mut rng).is_none())
+            .count();
+        assert!(absorbed > 0);
+    }
+
+    #[test]
+    fn metal_clamps_fuzz() {
+        let material = Metal::new(Vector3::new(0.8, 0.8, 0.8), 3.0);
+        assert_eq!(material.fuzz, 1.0);
+    }
+
+    #[test]
+    fn normal_attenuation() {
+        let hit = hit();
+        let (attenuation, scattered) =
+            Normal.scatter(&ray(), &hit, &mut Rng::default()).unwrap();
+
+        assert_eq!(
+            attenuation,
+            Vector3::new(0.5, 0.5, 1.0)
+        );
+        assert_eq!(scattered.direction, hit.normal);
+    }
+
+    #[test]
+    fn lambertian_scatters_into_hemisphere() {
+        let hit = hit();
+        let material = Lambertian::new(Vector3::new(0.5, 0.5, 0.5));
+        let mut rng = Rng::default();
+
+        for _ in 0..1000 {
+            let (attenuation, scattered) =
+                material.scatter(&ray(), &hit, &mut rng).unwrap();
+
+            assert_eq!(attenuation, material.albedo);
+            assert_eq!(scattered.origin, hit.position);
+            assert!(scattered.direction.dot(hit.normal) >= 0.0);
+        }
+    }
+}
@@ -0,0 +1,129 @@
+use std::any::Any;
+use std::ops::RangeInclusive;
+
+use super::bvh::BvhNode;
+use super::bvh::SplitMethod;
+use super::hittable::*;
+use super::triangle::Triangle;
+use crate::math::aabb::Aabb;
+use crate::math::ray::Ray;
+
+/// Triangles sharing their own bounding volume hierarchy, added to a
+/// `World` as a single object.
+pub struct Mesh {
+    bvh: BvhNode,
+    triangles: usize,
+}
+
+impl Mesh {
+    /// Builds the hierarchy over `triangles` with the surface area
+    /// heuristic.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `triangles` is empty.
+    pub fn new(triangles: Vec<Triangle>) -> Self {
+        let count = triangles.len();
+        let objects = triangles
+            .into_iter()
+            .map(|triangle| Box::new(triangle) as Box<dyn Hittable>)
+            .collect();
+
+        Self {
+            bvh: BvhNode::with_split(
+                objects,
+                SplitMethod::Sah { bins: 16 },
+            ),
+            triangles: count,
+        }
+    }
+
+    pub fn triangle_count(&self) -> usize {
+        self.triangles
+    }
+
+    /// Returns the triangles in the order of the hierarchy's leaves.
+    pub fn triangles(&self) -> Vec<&Triangle> {
+        self.bvh
+            .objects()
+            .into_iter()
+            .filter_map(|object| (object as &dyn Any).downcast_ref())
+            .collect()
+    }
+}
+
+impl Hittable for Mesh {
+    fn hit(
+        &self,
+        ray: &Ray,
+        valid_range: RangeInclusive<f64>,
+    ) -> Option<Hit<'_>> {
+        self.bvh.hit(ray, valid_range)
+    }
+
+    fn bounding_box(&self) -> Option<Aabb> {
+        self.bvh.bounding_box()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::math::vector3::Vector3;
+    use crate::utils::material::Normal;
+    use std::sync::Arc;
+
+    /// Unit square in the z = -1 plane made of two triangles.
+    fn square() -> Mesh {
+        let material = Arc::new(Normal);
+        let corners = [
+            Vector3::new(0.0, 0.0, -1.0),
+            Vector3::new(1.0, 0.0, -1.0),
+            Vector3::new(1.0, 1.0, -1.0),
+            Vector3::new(0.0, 1.0, -1.0),
+        ];
+
+        Mesh::new(vec![
+            Triangle::new(
+                corners[0],
+                corners[1],
+                corners[2],
+                material.clone(),
+            ),
+            Triangle::new(
+                corners[0], corners[2], corners[3], material,
+            ),
+        ])
+    }
+
+    #[test]
+    fn hits_both_halves() {
+        let mesh = square();
+
+        for (x, y) in [(0.75, 0.25), (0.25, 0.75)] {
+            let ray = Ray::new(
+                Vector3::new(x, y, 0.0),
+                Vector3::new(0.0, 0.0, -1.0),
+            );
+            assert_eq!(
+                mesh.hit(&ray, 0.0..=f64::MAX).unwrap().t,
+                1.0
+            );
+        }
+
+        let ray = Ray::new(
+            Vector3::new(1.5, 0.5, 0.0),
+            Vector3::new(0.0, 0.0, -1.0),
+        );
+        assert!(mesh.hit(&ray, 0.0..=f64::MAX).is_none());
+    }
+
+    #[test]
+    fn describes_every_triangle() {
+        let mesh = square();
+
+        assert_eq!(mesh.triangle_count(), 2);
+        assert_eq!(mesh.triangles().len(), 2);
+        assert!(mesh.bounding_box().is_some());
+    }
+}
@@ -0,0 +1,847 @@
+//! Wavefront OBJ and MTL loading.
+//!
+//! Vertices, normals, texture coordinates and polygonal faces are read,
+//! faces with more than three vertices are split into a fan around their
+//! first vertex. Indices count from 1, negative indices count back from the
+//! last element defined so far. Statements that don't affect the surface,
+//! such as groups, smoothing groups and lines, are ignored.
+
+use std::collections::HashMap;
+use std::path::Path;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use super::material::Dielectric;
+use super::material::DiffuseLight;
+use super::material::Lambertian;
+use super::material::Material;
+use super::material::Metal;
+use super::mesh::Mesh;
+use super::triangle::Triangle;
+use crate::math::vector3::Vector3;
+
+/// Materials by name, as defined by `newmtl`.
+pub type Materials = HashMap<String, Arc<dyn Material>>;
+
+/// Error returned when an OBJ or MTL file can't be loaded.
+#[derive(Debug)]
+pub enum ObjError {
+    Io(std::io::Error),
+    /// A problem on the 1 based `line`.
+    Syntax {
+        line: usize,
+        message: String,
+    },
+    /// The file has no faces.
+    NoFaces,
+    /// A material library referenced by `mtllib` can't be read or is
+    /// invalid.
+    Library {
+        path: PathBuf,
+        error: Box<ObjError>,
+    },
+}
+
+impl std::fmt::Display for ObjError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            ObjError::Io(error) => write!(f, "{}", error),
+            ObjError::Syntax { line, message } => {
+                write!(f, "line {}: {}", line, message)
+            }
+            ObjError::NoFaces => write!(f, "the model has no faces"),
+            ObjError::Library { path, error } => {
+                write!(f, "{}: {}", path.display(), error)
+            }
+        }
+    }
+}
+
+impl std::error::Error for ObjError {}
+
+impl From<std::io::Error> for ObjError {
+    fn from(error: std::io::Error) -> Self {
+        ObjError::Io(error)
+    }
+}
+
+/// Loads the model at `path` as a single mesh.
+///
+/// Material libraries are looked up next to the model and must exist.
+/// Faces before the first `usemtl` use `default`.
+pub fn load_obj(
+    path: &Path,
+    default: Arc<dyn Material>,
+) -> Result<Mesh, ObjError> {
+    let source = std::fs::read_to_string(path)?;
+    let directory = path.parent().unwrap_or_else(|| Path::new(""));
+    let materials = load_libraries(&source, directory)?;
+
+    parse_obj(&source, &materials, default)
+}
+
+/// Loads every library named by the `mtllib` statements of a model,
+/// relative to `directory`.
+fn load_libraries(
+    source: &str,
+    directory: &Path,
+) -> Result<Materials, ObjError> {
+    let mut materials = Materials::new();
+    for (_, rest) in
+        statements(source).filter(|(keyword, _)| *keyword == "mtllib")
+    {
+        for name in rest.split_whitespace() {
+            let path = directory.join(name);
+            let parsed = std::fs::read_to_string(&path)
+                .map_err(ObjError::Io)
+                .and_then(|source| parse_mtl(&source))
+                .map_err(|error| ObjError::Library {
+                    path,
+                    error: Box::new(error),
+                })?;
+            materials.extend(parsed);
+        }
+    }
+
+    Ok(materials)
+}
+
+/// Parses a model, `usemtl` looks materials up in `materials` and faces
+/// before the first one use `default`. `mtllib` statements are ignored.
+pub fn parse_obj(
+    source: &str,
+    materials: &Materials,
+    default: Arc<dyn Material>,
+) -> Result<Mesh, ObjError> {
+    let mut positions = Vec::new();
+    let mut normals = Vec::new();
+    let mut texcoords = Vec::new();
+    let mut triangles = Vec::new();
+    let mut material = default.clone();
+
+    for (index, line) in source.lines().enumerate() {
+        let error = |message: String| ObjError::Syntax {
+            line: index + 1,
+            message,
+        };
+        let (keyword, rest) = match statement(line) {
+            Some(statement) => statement,
+            None => continue,
+        };
+
+        match keyword {
+            "v" => positions.push(vector(rest).map_err(error)?),
+            "vn" => normals.push(vector(rest).map_err(error)?),
+            // The third texture coordinate is optional and unused.
+            "vt" => {
+                let values = numbers(rest).map_err(error)?;
+                if values.is_empty() || values.len() > 3 {
+                    return Err(error(
+                        "expected one to three texture coordinates"
+                            .to_string(),
+                    ));
+                }
+                texcoords.push((
+                    values[0],
+                    values.get(1).copied().unwrap_or(0.0),
+                ));
+            }
+            "usemtl" => {
+                let name = rest.trim();
+                if name.is_empty() {
+                    return Err(error(
+                        "expected a material name".to_string(),
+                    ));
+                }
+                material = materials.get(name).cloned().ok_or_else(|| {
+                    error(format!(
+                        "unknown material `{}`",
+                        name
+                    ))
+                })?;
+            }
+            "f" => {
+                let corners = rest
+                    .split_whitespace()
+                    .map(|corner| {
+                        Corner::parse(
+                            corner,
+                            positions.len(),
+                            texcoords.len(),
+                            normals.len(),
+                        )
+                    })
+                    .collect::<Result<Vec<_>, _>>()
+                    .map_err(error)?;
+                if corners.len() < 3 {
+                    return Err(error(
+                        "a face needs at least three vertices".to_string(),
+                    ));
+                }
+
+                for i in 1..corners.len() - 1 {
+                    let corners = [corners[0], corners[i], corners[i + 1]];
+                    let mut triangle = Triangle::new(
+                        positions[corners[0].position],
+                        positions[corners[1].position],
+                        positions[corners[2].position],
+                        material.clone(),
+                    );
+
+                    if let [Some(a), Some(b), Some(c)] =
+                        corners.map(|corner| corner.normal)
+                    {
+                        triangle.normals =
+                            Some([normals[a], normals[b], normals[c]]);
+                    }
+                    if let [Some(a), Some(b), Some(c)] =
+                        corners.map(|corner| corner.texcoord)
+                    {
+                        triangle.texcoords =
+                            Some([texcoords[a], texcoords[b], texcoords[c]]);
+                    }
+
+                    triangles.push(triangle);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    if triangles.is_empty() {
+        return Err(ObjError::NoFaces);
+    }
+
+    Ok(Mesh::new(triangles))
+}
+
+/// Parses a material library.
+///
+/// Emissive materials (`Ke`) become lights, transparent ones (`d` below 1,
+/// `Tr` above 0 or a refractive `illum`) dielectrics with index `Ni`, and
+/// reflective ones (`illum 3`) metals tinted by `Ks` with a fuzz derived
+/// from the `Ns` exponent. Everything else is Lambertian with albedo `Kd`.
+pub fn parse_mtl(source: &str) -> Result<Materials, ObjError> {
+    let mut descriptions: Vec<(String, Description)> = Vec::new();
+
+    for (index, line) in source.lines().enumerate() {
+        let error = |message: String| ObjError::Syntax {
+            line: index + 1,
+            message,
+        };
+        let (keyword, rest) = match statement(line) {
+            Some(statement) => statement,
+            None => continue,
+        };
+
+        if keyword == "newmtl" {
+            let name = rest.trim();
+            if name.is_empty() {
+                return Err(error(
+                    "expected a material name".to_string(),
+                ));
+            }
+            descriptions.push((
+                name.to_string(),
+                Description::default(),
+            ));
+            continue;
+        }
+
+        let current = match descriptions.last_mut() {
+            Some((_, description)) => description,
+            None => {
+                return Err(error(format!(
+                    "`{}` before `newmtl`",
+                    keyword
+                )))
+            }
+        };
+
+        match keyword {
+            "Kd" => current.diffuse = vector(rest).map_err(error)?,
+            "Ks" => current.specular = vector(rest).map_err(error)?,
+            "Ke" => current.emission = vector(rest).map_err(error)?,
+            "Ns" => current.exponent = scalar(rest).map_err(error)?,
+            "Ni" => current.ior = scalar(rest).map_err(error)?,
+            "d" => current.dissolve = scalar(rest).map_err(error)?,
+            "Tr" => current.dissolve = 1.0 - scalar(rest).map_err(error)?,
+            "illum" => {
+                current.illumination = rest.trim().parse().map_err(|_| {
+                    error(format!(
+                        "invalid illumination model `{}`",
+                        rest.trim()
+                    ))
+                })?
+            }
+            _ => {}
+        }
+    }
+
+    Ok(descriptions
+        .into_iter()
+        .map(|(name, description)| (name, description.material()))
+        .collect())
+}
+
+/// Material properties read from an MTL file.
+struct Description {
+    diffuse: Vector3,
+    specular: Vector3,
+    emission: Vector3,
+    exponent: f64,
+    ior: f64,
+    dissolve: f64,
+    illumination: u32,
+}
+
+impl Default for Description {
+    fn default() -> Self {
+        Self {
+            diffuse: Vector3::new(0.8, 0.8, 0.8),
+            specular: Vector3::new(0.0, 0.0, 0.0),
+            emission: Vector3::new(0.0, 0.0, 0.0),
+            exponent: 0.0,
+            ior: 1.5,
+            dissolve: 1.0,
+            illumination: 2,
+        }
+    }
+}
+
+impl Description {
+    fn material(&self) -> Arc<dyn Material> {
+        let black = |color: Vector3| {
+            color.x <= 0.0 && color.y <= 0.0 && color.z <= 0.0
+        };
+
+        if !black(self.emission) {
+            Arc::new(DiffuseLight::new(self.emission))
+        } else if self.dissolve < 1.0
+            || matches!(self.illumination, 4 | 6 | 7 | 9)
+        {
+            Arc::new(Dielectric::new(self.ior))
+        } else if self.illumination == 3 {
+            let albedo = if black(self.specular) {
+                self.diffuse
+            } else {
+                self.specular
+            };
+            // Roughness of the Beckmann distribution matching the Phong
+            // exponent.
+            let fuzz = (2.0 / (self.exponent.max(0.0) + 2.0)).sqrt();
+            Arc::new(Metal::new(albedo, fuzz))
+        } else {
+            Arc::new(Lambertian::new(self.diffuse))
+        }
+    }
+}
+
+/// Indices of the data used by one vertex of a face.
+#[derive(Clone, Copy)]
+struct Corner {
+    position: usize,
+    texcoord: Option<usize>,
+    normal: Option<usize>,
+}
+
+impl Corner {
+    /// Parses `v`, `v/vt`, `v//vn` or `v/vt/vn` given how many of each
+    /// element have been defined.
+    fn parse(
+        corner: &str,
+        positions: usize,
+        texcoords: usize,
+        normals: usize,
+    ) -> Result<Self, String> {
+        let mut parts = corner.split('/');
+        let position = parts.next().unwrap_or("");
+        let texcoord = parts.next().filter(|part| !part.is_empty());
+        let normal = parts.next();
+        if parts.next().is_some() {
+            return Err(format!(
+                "invalid face vertex `{}`",
+                corner
+            ));
+        }
+
+        Ok(Self {
+            position: resolve(position, positions, "vertex")?,
+            texcoord: texcoord
+                .map(|index| {
+                    resolve(
+                        index,
+                        texcoords,
+                        "texture coordinate",
+                    )
+                })
+                .transpose()?,
+            normal: normal
+                .map(|index| resolve(index, normals, "normal"))
+                .transpose()?,
+        })
+    }
+}
+
+/// Turns a 1 based or negative relative index into a 0 based one.
+fn resolve(index: &str, count: usize, what: &str) -> Result<usize, String> {
+    let parsed: i64 = index.parse().map_err(|_| {
+        format!(
+            "invalid {} index `{}`",
+            what, index
+        )
+    })?;
+
+    let resolved = match parsed {
+        0 => None,
+        1.. => usize::try_from(parsed - 1).ok(),
+        _ => count.checked_sub(parsed.unsigned_abs() as usize),
+    };
+
+    resolved
+        .filter(|resolved| *resolved < count)
+        .ok_or_else(|| {
+            format!(
+                "{} index {} is out of range",
+                what, parsed
+            )
+        })
+}
+
+/// Splits off the keyword of a line, skipping blank lines and comments.
+fn statement(line: &str) -> Option<(&str, &str)> {
+    let line = line.split('#').next().unwrap_or("").trim();
+    if line.is_empty() {
+        return None;
+    }
+
+    Some(line.split_once(char::is_whitespace).unwrap_or((line, "")))
+}
+
+fn statements(source: &str) -> impl Iterator<Item = (&str, &str)> {
+    source.lines().filter_map(statement)
+}
+
+fn numbers(text: &str) -> Result<Vec<f64>, String> {
+    text.split_whitespace()
+        .map(|token| {
+            token
+                .parse()
+                .ok()
+                .filter(|number: &f64| number.is_finite())
+                .ok_or_else(|| {
+                    format!(
+                        "expected a number, found `{}`",
+                        token
+                    )
+                })
+        })
+        .collect()
+}
+
+fn scalar(text: &str) -> Result<f64, String> {
+    match numbers(text)?[..] {
+        [value] => Ok(value),
+        _ => Err("expected a single number".to_string()),
+    }
+}
+
+/// Parses three numbers, ignoring trailing ones such as the vertex weight
+/// or vertex colors some exporters write.
+fn vector(text: &str) -> Result<Vector3, String> {
+    let values = numbers(text)?;
+    if values.len() < 3 {
+        return Err("expected three numbers".to_string());
+    }
+
+    Ok(Vector3::new(
+        values[0], values[1], values[2],
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::math::ray::Ray;
+    use crate::utils::hittable::Hit;
+    use crate::utils::hittable::Hittable;
+    use crate::utils::material::Normal;
+
+    fn parse(source: &str) -> Result<Mesh, ObjError> {
+        parse_obj(
+            source,
+            &Materials::new(),
+            Arc::new(Normal),
+        )
+    }
+
+    fn syntax_error(source: &str) -> (usize, String) {
+        match parse(source) {
+            Err(ObjError::Syntax { line, message }) => (line, message),
+            Err(error) => panic!(
+                "expected a syntax error, got {}",
+                error
+            ),
+            Ok(_) => panic!("expected a syntax error"),
+        }
+    }
+
+    /// Casts a ray straight down the -z axis through `(x, y)`.
+    fn hit_at(mesh: &Mesh, x: f64, y: f64) -> Option<Hit<'_>> {
+        let ray = Ray::new(
+            Vector3::new(x, y, 0.0),
+            Vector3::new(0.0, 0.0, -1.0),
+        );
+        mesh.hit(&ray, 0.0..=f64::MAX)
+    }
+
+    /// Returns the material as a `T`, if it is one.
+    fn downcast<T: Material>(material: &dyn Material) -> Option<&T> {
+        (material as &dyn std::any::Any).downcast_ref()
+    }
+
+    #[test]
+    fn polygons_are_fan_triangulated() {
+        let mesh = parse(
+            "v 0 0 -1\nv 1 0 -1\nv 1 1 -1\nv 0 1 -1\nv -1 0.5 -1\n\
+             f 1 2 3 4\n\
+             f 1 2 3 4 5\n",
+        )
+        .unwrap();
+
+        assert_eq!(mesh.triangle_count(), 5);
+        assert!(hit_at(&mesh, 0.25, 0.75).is_some());
+        assert!(hit_at(&mesh, -0.5, 0.5).is_some());
+        assert!(hit_at(&mesh, 1.5, 0.5).is_none());
+    }
+
+    #[test]
+    fn negative_indices_count_back() {
+        let mesh = parse(
+            "v 5 5 5\n\
+             v 0 0 -1\nv 1 0 -1\nv 0 1 -1\n\
+             f -3 -2 -1\n\
+             v 9 9 9\n",
+        )
+        .unwrap();
+
+        assert_eq!(
+            hit_at(&mesh, 0.25, 0.25).unwrap().t,
+            1.0
+        );
+    }
+
+    #[test]
+    fn face_vertex_forms() {
+        let source = "v 0 0 -1\nv 1 0 -1\nv 0 1 -1\n\
+                      vt 0 0\nvt 1 0\nvt 0 1 0\n\
+                      vn 0 0.6 0.8\n";
+
+        let flat = Vector3::new(0.0, 0.0, 1.0);
+        let smooth = Vector3::new(0.0, 0.6, 0.8);
+
+        for (face, normal) in [
+            ("f 1 2 3", flat),
+            ("f 1/1 2/2 3/3", flat),
+            ("f 1//1 2//1 3//1", smooth),
+            ("f 1/1/1 2/2/1 3/3/1", smooth),
+        ] {
+            let mesh = parse(&format!("{}{}\n", source, face)).unwrap();
+            let hit = hit_at(&mesh, 0.25, 0.25).unwrap();
+
+            assert!(
+                (hit.normal - normal).near_zero(),
+                "{}",
+                face
+            );
+        }
+    }
+
+    #[test]
+    fn normals_need_every_vertex() {
+        let mesh = parse(
+            "v 0 0 -1\nv 1 0 -1\nv 0 1 -1\nvn 0 0.6 0.8\n\
+             f 1//1 2 3//1\n",
+        )
+        .unwrap();
+
+        assert_eq!(
+            hit_at(&mesh, 0.25, 0.25).unwrap().normal,
+            Vector3::new(0.0, 0.0, 1.0)
+        );
+    }
+
+    #[test]
+    fn ignores_unsupported_statements() {
+        let mesh = parse(
+            "# comment\no model\ng group\ns 1\n\
+             v 0 0 -1 1.0\nv 1 0 -1\nv 0 1 -1\n\
+             l 1 2\nf 1 2 3 # trailing comment\n",
+        )
+        .unwrap();
+
+        assert_eq!(mesh.triangle_count(), 1);
+    }
+
+    #[test]
+    fn invalid_models() {
+        let vertices = "v 0 0 -1\nv 1 0 -1\nv 0 1 -1\n";
+
+        assert_eq!(
+            syntax_error("v 0 0\n"),
+            (
+                1,
+                "expected three numbers".to_string()
+            )
+        );
+        assert_eq!(
+            syntax_error("v 0 zero 0\n"),
+            (
+                1,
+                "expected a number, found `zero`".to_string()
+            )
+        );
+        assert_eq!(
+            syntax_error(&format!("{}f 1 2\n", vertices)),
+            (
+                4,
+                "a face needs at least three vertices".to_string()
+            )
+        );
+        assert_eq!(
+            syntax_error(&format!("{}f 1 2 4\n", vertices)),
+            (
+                4,
+                "vertex index 4 is out of range".to_string()
+            )
+        );
+        assert_eq!(
+            syntax_error(&format!("{}f 0 1 2\n", vertices)),
+            (
+                4,
+                "vertex index 0 is out of range".to_string()
+            )
+        );
+        assert_eq!(
+            syntax_error(&format!("{}f -4 1 2\n", vertices)),
+            (
+                4,
+                "vertex index -4 is out of range".to_string()
+            )
+        );
+        assert_eq!(
+            syntax_error(&format!(
+                "{}f 1/1 2/1 3/1\n",
+                vertices
+            )),
+            (
+                4,
+                "texture coordinate index 1 is out of range".to_string()
+            )
+        );
+        assert_eq!(
+            syntax_error(&format!("{}f 1/a 2 3\n", vertices)),
+            (
+                4,
+                "invalid texture coordinate index `a`".to_string()
+            )
+        );
+        assert_eq!(
+            syntax_error(&format!(
+                "{}f 1/1/1/1 2 3\n",
+                vertices
+            )),
+            (
+                4,
+                "invalid face vertex `1/1/1/1`".to_string()
+            )
+        );
+        assert!(matches!(
+            parse(vertices),
+            Err(ObjError::NoFaces)
+        ));
+    }
+
+    #[test]
+    fn materials_by_name() {
+        let materials = parse_mtl(
+            "# library\n\
+             newmtl plain\n\
+             newmtl red\nKd 0.8 0.1 0.1\n\
+             newmtl mirror\nKs 0.9 0.9 0.9\nNs 1000\nillum 3\n\
+             newmtl glass\nNi 1.33\nd 0.5\n\
+             newmtl tinted\nTr 0.2\n\
+             newmtl lamp\nKe 4 4 4\nd 0.5\n",
+        )
+        .unwrap();
+
+        let material = |name: &str| &*materials[name];
+
+        let plain = downcast::<Lambertian>(material("plain")).unwrap();
+        assert_eq!(
+            plain.albedo,
+            Vector3::new(0.8, 0.8, 0.8)
+        );
+        let red = downcast::<Lambertian>(material("red")).unwrap();
+        assert_eq!(
+            red.albedo,
+            Vector3::new(0.8, 0.1, 0.1)
+        );
+
+        let mirror = downcast::<Metal>(material("mirror")).unwrap();
+        assert_eq!(
+            mirror.albedo,
+            Vector3::new(0.9, 0.9, 0.9)
+        );
+        assert!(mirror.fuzz > 0.0 && mirror.fuzz < 0.05);
+
+        let glass = downcast::<Dielectric>(material("glass")).unwrap();
+        assert_eq!(glass.ior, 1.33);
+        assert!(downcast::<Dielectric>(material("tinted")).is_some());
+
+        let lamp = downcast::<DiffuseLight>(material("lamp")).unwrap();
+        assert_eq!(
+            lamp.emit,
+            Vector3::new(4.0, 4.0, 4.0)
+        );
+    }
+
+    #[test]
+    fn invalid_material_libraries() {
+        let error = |source: &str| match parse_mtl(source) {
+            Err(ObjError::Syntax { line, message }) => (line, message),
+            _ => panic!("expected a syntax error"),
+        };
+
+        assert_eq!(
+            error("Kd 1 1 1\n"),
+            (
+                1,
+                "`Kd` before `newmtl`".to_string()
+            )
+        );
+        assert_eq!(
+            error("newmtl\n"),
+            (
+                1,
+                "expected a material name".to_string()
+            )
+        );
+        assert_eq!(
+            error("newmtl a\nNs 1 2\n"),
+            (
+                2,
+                "expected a single number".to_string()
+            )
+        );
+        assert_eq!(
+            error("newmtl a\nillum two\n"),
+            (
+                2,
+                "invalid illumination model `two`".to_string()
+            )
+        );
+    }
+
+    #[test]
+    fn usemtl_selects_material() {
+        let mut materials = Materials::new();
+        materials.insert(
+            "lamp".to_string(),
+            Arc::new(DiffuseLight::new(Vector3::new(
+                1.0, 1.0, 1.0,
+            ))),
+        );
+        let parse = |source: &str| {
+            parse_obj(
+                source,
+                &materials,
+                Arc::new(Normal),
+            )
+        };
+        let mesh = parse(
+            "v 0 0 -1\nv 1 0 -1\nv 0 1 -1\n\
+             v 0 0 -2\nv -1 0 -2\nv 0 -1 -2\n\
+             f 4 5 6\n\
+             usemtl lamp\nf 1 2 3\n",
+        )
+        .unwrap();
+
+        let lights = [(0.25, 0.25), (-0.25, -0.25)].map(|(x, y)| {
+            let material = hit_at(&mesh, x, y).unwrap().material;
+            downcast::<DiffuseLight>(material).is_some()
+        });
+        assert_eq!(lights, [true, false]);
+
+        let error = |source: &str| match parse(source) {
+            Err(ObjError::Syntax { line, message }) => (line, message),
+            _ => panic!("expected a syntax error"),
+        };
+        assert_eq!(
+            error("v 0 0 0\nusemtl lamp\nusemtl missing\n"),
+            (
+                3,
+                "unknown material `missing`".to_string()
+            )
+        );
+        assert_eq!(
+            error("usemtl\n"),
+            (
+                1,
+                "expected a material name".to_string()
+            )
+        );
+    }
+
+    #[test]
+    fn loads_example_model() {
+        let path =
+            Path::new(env!("CARGO_MANIFEST_DIR")).join("scenes/pyramid.obj");
+        let mesh = load_obj(&path, Arc::new(Normal)).unwrap();
+
+        assert_eq!(mesh.triangle_count(), 6);
+
+        // Looking down at the tip hits a gold side, looking up the stone
+        // base.
+        let down = Ray::new(
+            Vector3::new(0.1, 1.0, -1.0),
+            Vector3::new(0.0, -1.0, 0.0),
+        );
+        let up = Ray::new(
+            Vector3::new(0.1, -1.0, -1.0),
+            Vector3::new(0.0, 1.0, 0.0),
+        );
+        let material =
+            |ray: &Ray| mesh.hit(ray, 0.0..=f64::MAX).unwrap().material;
+        assert!(downcast::<Metal>(material(&down)).is_some());
+        assert!(downcast::<Lambertian>(material(&up)).is_some());
+    }
+
+    #[test]
+    fn missing_library() {
+        let scenes = Path::new(env!("CARGO_MANIFEST_DIR")).join("scenes");
+        assert!(
+            load_libraries("mtllib pyramid.mtl\n", &scenes)
+                .unwrap()
+                .contains_key("gold")
+        );
+
+        match load_libraries("mtllib missing.mtl\n", &scenes) {
+            Err(ObjError::Library { path, error }) => {
+                assert_eq!(path, scenes.join("missing.mtl"));
+                assert!(matches!(*error, ObjError::Io(_)));
+            }
+            _ => panic!("expected a library error"),
+        }
+    }
+
+    #[test]
+    fn missing_model() {
+        let path =
+            Path::new(env!("CARGO_MANIFEST_DIR")).join("scenes/missing.obj");
+        assert!(matches!(
+            load_obj(&path, Arc::new(Normal)),
+            Err(ObjError::Io(_))
+        ));
+    }
+}
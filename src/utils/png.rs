@@ -0,0 +1,543 @@
+use super::ppm::PPM;
+use std::io::Write;
+
+/// How the image data is deflated.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Compression {
+    /// Uncompressed stored blocks, fastest to write.
+    Stored,
+    /// LZ77 with the fixed Huffman codes.
+    Fixed,
+}
+
+const SIGNATURE: [u8; 8] = [0x89, b'P', b'N', b'G', b'\r', b'\n', 0x1A, b'\n'];
+
+/// Writes the image as an 8 bit RGB PNG.
+pub fn write<W: Write>(
+    ppm: &PPM,
+    mut writer: W,
+    compression: Compression,
+) -> std::io::Result<()> {
+    let mut header = Vec::with_capacity(13);
+    header.extend_from_slice(&(ppm.columns() as u32).to_be_bytes());
+    header.extend_from_slice(&(ppm.rows() as u32).to_be_bytes());
+    // Bit depth 8, truecolor, deflate, no filtering, no interlacing.
+    header.extend_from_slice(&[8, 2, 0, 0, 0]);
+
+    // Every scanline starts with its filter type, always none.
+    let mut raw = Vec::with_capacity((ppm.columns() * 3 + 1) * ppm.rows());
+    for row in ppm.pixels.chunks(ppm.columns().max(1)) {
+        raw.push(0);
+        for pixel in row {
+            raw.extend_from_slice(&pixel.to_array());
+        }
+    }
+
+    writer.write_all(&SIGNATURE)?;
+    write_chunk(&mut writer, b"IHDR", &header)?;
+    write_chunk(
+        &mut writer,
+        b"IDAT",
+        &zlib(&raw, compression),
+    )?;
+    write_chunk(&mut writer, b"IEND", &[])?;
+
+    writer.flush()
+}
+
+fn write_chunk<W: Write>(
+    writer: &mut W,
+    kind: &[u8; 4],
+    data: &[u8],
+) -> std::io::Result<()> {
+    let mut crc = Crc32::new();
+    crc.update(kind);
+    crc.update(data);
+
+    writer.write_all(&(data.len() as u32).to_be_bytes())?;
+    writer.write_all(kind)?;
+    writer.write_all(data)?;
+    writer.write_all(&crc.finish().to_be_bytes())
+}
+
+/// Wraps deflated `data` in a zlib stream.
+pub fn zlib(data: &[u8], compression: Compression) -> Vec<u8> {
+    // Deflate with a 32K window, the check bits make the header a
+    // multiple of 31.
+    let mut out = vec![0x78, 0x01];
+
+    match compression {
+        Compression::Stored => deflate_stored(data, &mut out),
+        Compression::Fixed => deflate_fixed(data, &mut out),
+    }
+
+    out.extend_from_slice(&adler32(data).to_be_bytes());
+    out
+}
+
+fn deflate_stored(data: &[u8], out: &mut Vec<u8>) {
+    let mut blocks = data.chunks(u16::MAX as usize).peekable();
+
+    if blocks.peek().is_none() {
+        out.extend_from_slice(&[1, 0, 0, 0xFF, 0xFF]);
+    }
+
+    while let Some(block) = blocks.next() {
+        let last = blocks.peek().is_none();
+        let len = block.len() as u16;
+
+        out.push(last as u8);
+        out.extend_from_slice(&len.to_le_bytes());
+        out.extend_from_slice(&(!len).to_le_bytes());
+        out.extend_from_slice(block);
+    }
+}
+
+/// Base lengths for the length codes 257 to 285.
+const LENGTH_BASE: [u16; 29] = [
+    3, 4, 5, 6, 7, 8, 9, 10, 11, 13, 15, 17, 19, 23, 27, 31, 35, 43, 51, 59,
+    67, 83, 99, 115, 131, 163, 195, 227, 258,
+];
+const LENGTH_EXTRA: [u8; 29] = [
+    0, 0, 0, 0, 0, 0, 0, 0, 1, 1, 1, 1, 2, 2, 2, 2, 3, 3, 3, 3, 4, 4, 4, 4, 5,
+    5, 5, 5, 0,
+];
+/// Base distances for the distance codes 0 to 29.
+const DISTANCE_BASE: [u16; 30] = [
+    1, 2, 3, 4, 5, 7, 9, 13, 17, 25, 33, 49, 65, 97, 129, 193, 257, 385, 513,
+    769, 1025, 1537, 2049, 3073, 4097, 6145, 8193, 12289, 16385, 24577,
+];
+const DISTANCE_EXTRA: [u8; 30] = [
+    0, 0, 0, 0, 1, 1, 2, 2, 3, 3, 4, 4, 5, 5, 6, 6, 7, 7, 8, 8, 9, 9, 10, 10,
+    11, 11, 12, 12, 13, 13,
+];
+
+const WINDOW_SIZE: usize = 1 << 15;
+const MIN_MATCH: usize = 3;
+const MAX_MATCH: usize = 258;
+const MAX_CHAIN: usize = 64;
+const HASH_BITS: u32 = 15;
+
+/// Packs bits least significant first, as deflate expects.
+struct BitWriter<'a> {
+    out: &'a mut Vec<u8>,
+    buffer: u32,
+    count: u32,
+}
+
+impl<'a> BitWriter<'a> {
+    fn write(&mut self, bits: u32, count: u32) {
+        self.buffer |= bits << self.count;
+        self.count += count;
+
+        while self.count >= 8 {
+            self.out.push(self.buffer as u8);
+            self.buffer >>= 8;
+            self.count -= 8;
+        }
+    }
+
+    /// Huffman codes are stored most significant bit first.
+    fn write_code(&mut self, code: u32, count: u32) {
+        let reversed = code.reverse_bits() >> (32 - count);
+        self.write(reversed, count);
+    }
+
+    fn flush(&mut self) {
+        if self.count > 0 {
+            self.out.push(self.buffer as u8);
+        }
+        self.buffer = 0;
+        self.count = 0;
+    }
+
+    /// Writes a literal or length symbol with the fixed code.
+    fn write_symbol(&mut self, symbol: u16) {
+        let symbol = symbol as u32;
+        match symbol {
+            0..=143 => self.write_code(0x30 + symbol, 8),
+            144..=255 => self.write_code(0x190 + symbol - 144, 9),
+            256..=279 => self.write_code(symbol - 256, 7),
+            _ => self.write_code(0xC0 + symbol - 280, 8),
+        }
+    }
+
+    fn write_match(&mut self, length: usize, distance: usize) {
+        let code = LENGTH_BASE
+            .iter()
+            .rposition(|base| *base as usize <= length)
+            .unwrap();
+        self.write_symbol(257 + code as u16);
+        self.write(
+            (length - LENGTH_BASE[code] as usize) as u32,
+            LENGTH_EXTRA[code] as u32,
+        );
+
+        let code = DISTANCE_BASE
+            .iter()
+            .rposition(|base| *base as usize <= distance)
+            .unwrap();
+        self.write_code(code as u32, 5);
+        self.write(
+            (distance - DISTANCE_BASE[code] as usize) as u32,
+            DISTANCE_EXTRA[code] as u32,
+        );
+    }
+}
+
+/// Hashes the three bytes starting at `i`.
+fn hash(data: &[u8], i: usize) -> usize {
+    let key = (data[i] as u32) << 16
+        | (data[i + 1] as u32) << 8
+        | data[i + 2] as u32;
+    (key.wrapping_mul(0x9E37_79B1) >> (32 - HASH_BITS)) as usize
+}
+
+/// Compresses `data` as one fixed Huffman block, finding matches with hash
+/// chains over the previous 32K.
+fn deflate_fixed(data: &[u8], out: &mut Vec<u8>) {
+    let mut bits = BitWriter {
+        out,
+        buffer: 0,
+        count: 0,
+    };
+    // Final block, fixed Huffman codes.
+    bits.write(0b011, 3);
+
+    // Most recent position of every hash and, for the positions inside the
+    // window, the position before it with the same hash. Positions further
+    // back are never followed, so their slots in the ring are reused.
+    let mut head = vec![usize::MAX; 1 << HASH_BITS];
+    let mut previous = vec![usize::MAX; WINDOW_SIZE];
+    let insert = |i: usize, head: &mut [usize], previous: &mut [usize]| {
+        if i + MIN_MATCH <= data.len() {
+            let h = hash(data, i);
+            previous[i & (WINDOW_SIZE - 1)] = head[h];
+            head[h] = i;
+        }
+    };
+
+    let mut i = 0;
+    while i < data.len() {
+        let mut best_length = 0;
+        let mut best_distance = 0;
+
+        if i + MIN_MATCH <= data.len() {
+            let max_length = MAX_MATCH.min(data.len() - i);
+            let mut candidate = head[hash(data, i)];
+            let mut chain = 0;
+
+            while candidate != usize::MAX
+                && i - candidate <= WINDOW_SIZE
+                && chain < MAX_CHAIN
+            {
+                let length = data[candidate..]
+                    .iter()
+                    .zip(&data[i..i + max_length])
+                    .take_while(|(a, b)| a == b)
+                    .count();
+
+                if length > best_length {
+                    best_length = length;
+                    best_distance = i - candidate;
+                    if length == max_length {
+                        break;
+                    }
+                }
+
+                candidate = previous[candidate & (WINDOW_SIZE - 1)];
+                chain += 1;
+            }
+        }
+
+        if best_length >= MIN_MATCH {
+            bits.write_match(best_length, best_distance);
+            for j in i..i + best_length {
+                insert(j, &mut head, &mut previous);
+            }
+            i += best_length;
+        } else {
+            bits.write_symbol(data[i] as u16);
+            insert(i, &mut head, &mut previous);
+            i += 1;
+        }
+    }
+
+    bits.write_symbol(256);
+    bits.flush();
+}
+
+/// Running CRC-32 as used by PNG chunks.
+struct Crc32 {
+    table: [u32; 256],
+    crc: u32,
+}
+
+impl Crc32 {
+    fn new() -> Self {
+        let mut table = [0; 256];
+        for (n, entry) in table.iter_mut().enumerate() {
+            let mut c = n as u32;
+            for _ in 0..8 {
+                c = if c & 1 == 1 {
+                    0xEDB8_8320 ^ (c >> 1)
+                } else {
+                    c >> 1
+                };
+            }
+            *entry = c;
+        }
+
+        Self {
+            table,
+            crc: 0xFFFF_FFFF,
+        }
+    }
+
+    fn update(&mut self, data: &[u8]) {
+        for byte in data {
+            let index = ((self.crc ^ *byte as u32) & 0xFF) as usize;
+            self.crc = self.table[index] ^ (self.crc >> 8);
+        }
+    }
+
+    fn finish(&self) -> u32 {
+        self.crc ^ 0xFFFF_FFFF
+    }
+}
+
+pub fn crc32(data: &[u8]) -> u32 {
+    let mut crc = Crc32::new();
+    crc.update(data);
+    crc.finish()
+}
+
+pub fn adler32(data: &[u8]) -> u32 {
+    const MODULO: u32 = 65521;
+    let mut a: u32 = 1;
+    let mut b: u32 = 0;
+
+    // 5552 bytes is the most that can be summed before `b` overflows.
+    for chunk in data.chunks(5552) {
+        for byte in chunk {
+            a += *byte as u32;
+            b += a;
+        }
+        a %= MODULO;
+        b %= MODULO;
+    }
+
+    b << 16 | a
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::ppm::RGBTriplet;
+
+    struct BitReader<'a> {
+        data: &'a [u8],
+        position: usize,
+    }
+
+    impl<'a> BitReader<'a> {
+        fn bits(&mut self, count: u32) -> u32 {
+            (0..count).fold(0, |value, i| {
+                let byte = self.data[self.position / 8];
+                let bit = (byte >> (self.position % 8)) & 1;
+                self.position += 1;
+                value | (bit as u32) << i
+            })
+        }
+
+        /// Reads a Huffman code, most significant bit first.
+        fn code(&mut self, count: u32) -> u32 {
+            (0..count).fold(0, |value, _| {
+                value << 1 | self.bits(1)
+            })
+        }
+
+        fn fixed_symbol(&mut self) -> u32 {
+            let mut code = self.code(7);
+            if code <= 23 {
+                return code + 256;
+            }
+
+            code = code << 1 | self.bits(1);
+            match code {
+                0x30..=0xBF => code - 0x30,
+                0xC0..=0xC7 => code - 0xC0 + 280,
+                _ => (code << 1 | self.bits(1)) - 0x190 + 144,
+            }
+        }
+    }
+
+    /// Minimal inflater for the blocks written above.
+    fn inflate(data: &[u8]) -> Vec<u8> {
+        let mut out: Vec<u8> = Vec::new();
+        let mut reader = BitReader { data, position: 0 };
+
+        loop {
+            let last = reader.bits(1);
+            let kind = reader.bits(2);
+
+            if kind == 0 {
+                let start = reader.position.div_ceil(8);
+                let len = u16::from_le_bytes([data[start], data[start + 1]])
+                    as usize;
+                out.extend_from_slice(&data[start + 4..start + 4 + len]);
+                reader.position = (start + 4 + len) * 8;
+            } else {
+                assert_eq!(kind, 1);
+                loop {
+                    let symbol = reader.fixed_symbol() as usize;
+                    if symbol < 256 {
+                        out.push(symbol as u8);
+                        continue;
+                    } else if symbol == 256 {
+                        break;
+                    }
+
+                    let code = symbol - 257;
+                    let length = LENGTH_BASE[code] as usize
+                        + reader.bits(LENGTH_EXTRA[code] as u32) as usize;
+                    let code = reader.code(5) as usize;
+                    let distance = DISTANCE_BASE[code] as usize
+                        + reader.bits(DISTANCE_EXTRA[code] as u32) as usize;
+
+                    for _ in 0..length {
+                        out.push(out[out.len() - distance]);
+                    }
+                }
+            }
+
+            if last == 1 {
+                return out;
+            }
+        }
+    }
+
+    fn sample_data() -> Vec<u8> {
+        (0..200_000u32)
+            .map(|i| ((i / 7) % 251) as u8 ^ (i % 3) as u8)
+            .collect()
+    }
+
+    #[test]
+    fn crc32_check_value() {
+        assert_eq!(crc32(b"123456789"), 0xCBF4_3926);
+    }
+
+    #[test]
+    fn adler32_check_value() {
+        assert_eq!(adler32(b"Wikipedia"), 0x11E6_0398);
+    }
+
+    #[test]
+    fn zlib_header_is_valid() {
+        let out = zlib(b"abc", Compression::Stored);
+        assert_eq!(
+            (out[0] as u16 * 256 + out[1] as u16) % 31,
+            0
+        );
+    }
+
+    #[test]
+    fn stored_round_trip() {
+        let data = sample_data();
+        let out = zlib(&data, Compression::Stored);
+
+        assert_eq!(
+            inflate(&out[2..out.len() - 4]),
+            data
+        );
+        assert_eq!(
+            out[out.len() - 4..],
+            adler32(&data).to_be_bytes()
+        );
+    }
+
+    #[test]
+    fn stored_empty() {
+        let out = zlib(&[], Compression::Stored);
+        assert!(inflate(&out[2..out.len() - 4]).is_empty());
+    }
+
+    #[test]
+    fn fixed_round_trip() {
+        let data = sample_data();
+        let out = zlib(&data, Compression::Fixed);
+
+        assert!(out.len() < data.len() / 2);
+        assert_eq!(
+            inflate(&out[2..out.len() - 4]),
+            data
+        );
+    }
+
+    #[test]
+    fn fixed_matches_across_whole_window() {
+        // Noise that only repeats exactly one window later.
+        let mut state = 1u32;
+        let block: Vec<u8> = (0..WINDOW_SIZE)
+            .map(|_| {
+                state = state.wrapping_mul(1_103_515_245).wrapping_add(12_345);
+                (state >> 16) as u8
+            })
+            .collect();
+        let data = [&block[..], &block[..], &block[..100]].concat();
+        let out = zlib(&data, Compression::Fixed);
+
+        assert!(out.len() < data.len() * 3 / 4);
+        assert_eq!(
+            inflate(&out[2..out.len() - 4]),
+            data
+        );
+    }
+
+    #[test]
+    fn fixed_round_trip_short() {
+        for data in [&b""[..], b"a", b"ab", b"abcabcabcabcabc"] {
+            let out = zlib(data, Compression::Fixed);
+            assert_eq!(
+                inflate(&out[2..out.len() - 4]),
+                data
+            );
+        }
+    }
+
+    #[test]
+    fn png_chunks() {
+        let mut ppm = PPM::new(2, 1);
+        ppm.set(0, 0, RGBTriplet::new(255, 0, 0));
+        ppm.set(1, 0, RGBTriplet::new(0, 0, 255));
+
+        let mut out = Vec::new();
+        write(&ppm, &mut out, Compression::Stored).unwrap();
+
+        assert_eq!(out[..8], SIGNATURE);
+        assert_eq!(&out[12..16], b"IHDR");
+        assert_eq!(
+            out[16..29],
+            [0, 0, 0, 2, 0, 0, 0, 1, 8, 2, 0, 0, 0]
+        );
+        assert_eq!(
+            out[29..33],
+            crc32(&out[12..29]).to_be_bytes()
+        );
+
+        let idat_length =
+            u32::from_be_bytes(out[33..37].try_into().unwrap()) as usize;
+        assert_eq!(&out[37..41], b"IDAT");
+        let idat = &out[41..41 + idat_length];
+        assert_eq!(
+            inflate(&idat[2..idat.len() - 4]),
+            [0, 255, 0, 0, 0, 0, 255]
+        );
+
+        assert_eq!(
+            out[out.len() - 12..],
+            [0, 0, 0, 0, b'I', b'E', b'N', b'D', 0xAE, 0x42, 0x60, 0x82]
+        );
+    }
+}
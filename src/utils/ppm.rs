@@ -1,6 +1,133 @@
+use super::color::Transfer;
 use crate::math::vector3::Vector3;
+use std::io::Read;
+use std::io::Write;
+
+/// Encoding used when writing a `PPM`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum PpmFormat {
+    /// Plain text `P3`, one pixel per line.
+    Ascii,
+    /// Raw `P6`, three bytes per pixel.
+    Binary,
+}
+
+/// Error returned when a PPM file can't be read.
+#[derive(Debug)]
+pub enum PpmError {
+    Io(std::io::Error),
+    /// The file doesn't start with `P3` or `P6`.
+    UnsupportedFormat(String),
+    /// A header field is missing or not a valid number.
+    InvalidHeader(String),
+    /// A sample is not a number or is larger than the maxval.
+    InvalidSample(String),
+    /// The file ends before every sample was read.
+    Truncated {
+        expected: usize,
+        found: usize,
+    },
+}
+
+impl std::fmt::Display for PpmError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PpmError::Io(error) => write!(f, "{}", error),
+            PpmError::UnsupportedFormat(magic) => {
+                write!(
+                    f,
+                    "unsupported PPM format `{}`",
+                    magic
+                )
+            }
+            PpmError::InvalidHeader(message) => {
+                write!(
+                    f,
+                    "invalid PPM header: {}",
+                    message
+                )
+            }
+            PpmError::InvalidSample(sample) => {
+                write!(
+                    f,
+                    "invalid PPM sample `{}`",
+                    sample
+                )
+            }
+            PpmError::Truncated { expected, found } => write!(
+                f,
+                "truncated PPM data: expected {} samples, found {}",
+                expected, found
+            ),
+        }
+    }
+}
+
+impl std::error::Error for PpmError {}
+
+impl From<std::io::Error> for PpmError {
+    fn from(error: std::io::Error) -> Self {
+        PpmError::Io(error)
+    }
+}
+
+/// Splits a PPM header into whitespace separated tokens, skipping
+/// comments.
+struct Tokens<'a> {
+    bytes: &'a [u8],
+    position: usize,
+}
+
+impl<'a> Tokens<'a> {
+    fn next(&mut self) -> Option<&'a [u8]> {
+        loop {
+            match self.bytes.get(self.position)? {
+                b'#' => {
+                    while !matches!(
+                        self.bytes.get(self.position),
+                        None | Some(b'\n' | b'\r')
+                    ) {
+                        self.position += 1;
+                    }
+                }
+                byte if byte.is_ascii_whitespace() => self.position += 1,
+                _ => break,
+            }
+        }
+
+        let start = self.position;
+        while self
+            .bytes
+            .get(self.position)
+            .is_some_and(|byte| !byte.is_ascii_whitespace() && *byte != b'#')
+        {
+            self.position += 1;
+        }
+
+        Some(&self.bytes[start..self.position])
+    }
+
+    /// Reads the next header field as a number.
+    fn number(&mut self, field: &str) -> Result<usize, PpmError> {
+        let token = self.next().ok_or_else(|| {
+            PpmError::InvalidHeader(format!("missing {}", field))
+        })?;
+
+        std::str::from_utf8(token)
+            .ok()
+            .and_then(|token| token.parse().ok())
+            .ok_or_else(|| {
+                PpmError::InvalidHeader(format!(
+                    "{} `{}` is not a number",
+                    field,
+                    String::from_utf8_lossy(token)
+                ))
+            })
+    }
+}
 
 /// Encodes a PPM file
+#[derive(PartialEq)]
 pub struct PPM {
     columns: usize,
     rows: usize,
@@ -34,10 +161,191 @@ impl PPM {
         new
     }
 
+    pub fn columns(&self) -> usize {
+        self.columns
+    }
+
+    pub fn rows(&self) -> usize {
+        self.rows
+    }
+
     /// Gets the `RGBTriplet` at the specified `column` and `row`.
     pub fn get(&self, column: usize, row: usize) -> Option<&RGBTriplet> {
         self.pixels.get(self.columns * row + column)
     }
+
+    /// Reads a `P3` or `P6` image, rescaling the samples to 0 to 255.
+    pub fn read<R: Read>(mut reader: R) -> Result<Self, PpmError> {
+        let mut bytes = Vec::new();
+        reader.read_to_end(&mut bytes)?;
+
+        Self::parse(&bytes)
+    }
+
+    /// Parses a `P3` or `P6` image from memory.
+    pub fn parse(bytes: &[u8]) -> Result<Self, PpmError> {
+        let mut tokens = Tokens { bytes, position: 0 };
+
+        let magic = tokens.next().unwrap_or_default();
+        let binary = match magic {
+            b"P3" => false,
+            b"P6" => true,
+            _ => {
+                return Err(PpmError::UnsupportedFormat(
+                    String::from_utf8_lossy(magic).into_owned(),
+                ))
+            }
+        };
+
+        let columns = tokens.number("width")?;
+        let rows = tokens.number("height")?;
+        let maxval = tokens.number("maxval")?;
+        if maxval == 0 || maxval > 65535 {
+            return Err(PpmError::InvalidHeader(format!(
+                "maxval {} is not between 1 and 65535",
+                maxval
+            )));
+        }
+
+        let expected = columns
+            .checked_mul(rows)
+            .and_then(|pixels| pixels.checked_mul(3))
+            .ok_or_else(|| {
+                PpmError::InvalidHeader(format!(
+                    "{}x{} is too large",
+                    columns, rows
+                ))
+            })?;
+
+        let samples = if binary {
+            // A single whitespace byte separates the header from the data.
+            let data = bytes.get(tokens.position + 1..).unwrap_or_default();
+            Self::binary_samples(data, maxval, expected)?
+        } else {
+            Self::ascii_samples(&mut tokens, maxval, expected)?
+        };
+
+        let scale =
+            |sample: usize| ((sample * 255 + maxval / 2) / maxval) as u8;
+        let pixels = samples
+            .chunks_exact(3)
+            .map(|rgb| {
+                RGBTriplet::new(
+                    scale(rgb[0]),
+                    scale(rgb[1]),
+                    scale(rgb[2]),
+                )
+            })
+            .collect();
+
+        Ok(Self {
+            columns,
+            rows,
+            pixels,
+        })
+    }
+
+    fn ascii_samples(
+        tokens: &mut Tokens,
+        maxval: usize,
+        expected: usize,
+    ) -> Result<Vec<usize>, PpmError> {
+        let mut samples = Vec::with_capacity(expected.min(tokens.bytes.len()));
+
+        while samples.len() < expected {
+            let token = tokens.next().ok_or(PpmError::Truncated {
+                expected,
+                found: samples.len(),
+            })?;
+
+            let sample = std::str::from_utf8(token)
+                .ok()
+                .and_then(|token| token.parse().ok())
+                .filter(|sample| *sample <= maxval)
+                .ok_or_else(|| {
+                    PpmError::InvalidSample(
+                        String::from_utf8_lossy(token).into_owned(),
+                    )
+                })?;
+            samples.push(sample);
+        }
+
+        Ok(samples)
+    }
+
+    /// Samples are one byte, or two big endian bytes if `maxval` is
+    /// larger than 255.
+    fn binary_samples(
+        data: &[u8],
+        maxval: usize,
+        expected: usize,
+    ) -> Result<Vec<usize>, PpmError> {
+        let width = if maxval > 255 { 2 } else { 1 };
+        let length = expected.checked_mul(width).ok_or_else(|| {
+            PpmError::InvalidHeader(format!(
+                "{} samples are too large",
+                expected
+            ))
+        })?;
+        if data.len() < length {
+            return Err(PpmError::Truncated {
+                expected,
+                found: data.len() / width,
+            });
+        }
+
+        data[..length]
+            .chunks_exact(width)
+            .map(|bytes| {
+                bytes.iter().fold(0, |sample, byte| {
+                    sample << 8 | *byte as usize
+                })
+            })
+            .map(|sample| {
+                if sample <= maxval {
+                    Ok(sample)
+                } else {
+                    Err(PpmError::InvalidSample(
+                        sample.to_string(),
+                    ))
+                }
+            })
+            .collect()
+    }
+
+    /// Streams the image to `writer` in the given `format`.
+    ///
+    /// The writer is not buffered, wrap it in a `BufWriter` if needed.
+    pub fn write<W: Write>(
+        &self,
+        mut writer: W,
+        format: PpmFormat,
+    ) -> std::io::Result<()> {
+        match format {
+            PpmFormat::Ascii => {
+                write!(
+                    writer,
+                    "P3\n{} {}\n255\n",
+                    self.columns, self.rows
+                )?;
+                for pixel in self.pixels.iter() {
+                    writeln!(writer, "{}", pixel)?;
+                }
+            }
+            PpmFormat::Binary => {
+                write!(
+                    writer,
+                    "P6\n{} {}\n255\n",
+                    self.columns, self.rows
+                )?;
+                for pixel in self.pixels.iter() {
+                    writer.write_all(&[pixel.r, pixel.g, pixel.b])?;
+                }
+            }
+        }
+
+        writer.flush()
+    }
 }
 
 impl std::fmt::Debug for PPM {
@@ -57,13 +365,13 @@ impl std::fmt::Debug for PPM {
     }
 }
 
-impl ToString for PPM {
-    fn to_string(&self) -> String {
-        format!("{:?}", self)
+impl std::fmt::Display for PPM {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:?}", self)
     }
 }
 
-#[derive(Copy, Clone)]
+#[derive(Copy, Clone, PartialEq)]
 pub struct RGBTriplet {
     r: u8,
     g: u8,
@@ -76,6 +384,23 @@ impl RGBTriplet {
         Self { r, g, b }
     }
 
+    /// Returns the red, green and blue values in that order.
+    pub fn to_array(&self) -> [u8; 3] {
+        [self.r, self.g, self.b]
+    }
+
+    /// Encodes linear radiance with `transfer` and quantizes it to 8
+    /// bits, clamping every channel to `[0, 1]` first.
+    pub fn from_radiance(radiance: Vector3, transfer: Transfer) -> Self {
+        let quantize = |value: f64| (255.999 * transfer.encode(value)) as u8;
+
+        Self {
+            r: quantize(radiance.x),
+            g: quantize(radiance.y),
+            b: quantize(radiance.z),
+        }
+    }
+
     pub fn from_vector3(vector: Vector3) -> Self {
         Self {
             r: vector.x as u8,
@@ -85,9 +410,13 @@ impl RGBTriplet {
     }
 }
 
-impl ToString for RGBTriplet {
-    fn to_string(&self) -> String {
-        format!("{} {} {}", self.r, self.g, self.b)
+impl std::fmt::Display for RGBTriplet {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} {} {}",
+            self.r, self.g, self.b
+        )
     }
 }
 
@@ -157,4 +486,161 @@ mod tests {
 
         assert_eq!(ppm.to_string(), cmp);
     }
+
+    fn three_by_two_ppm() -> PPM {
+        let mut ppm = PPM::new(3, 2);
+
+        ppm.set(0, 0, RGBTriplet::new(255, 0, 0));
+        ppm.set(1, 0, RGBTriplet::new(0, 255, 0));
+        ppm.set(2, 0, RGBTriplet::new(0, 0, 255));
+        ppm.set(0, 1, RGBTriplet::new(255, 255, 0));
+        ppm.set(
+            1,
+            1,
+            RGBTriplet::new(255, 255, 255),
+        );
+        ppm.set(2, 1, RGBTriplet::new(0, 0, 0));
+
+        ppm
+    }
+
+    #[test]
+    fn write_ascii() {
+        let cmp = read_to_string("./tests/three_by_two.ppm").unwrap();
+        let mut out = Vec::new();
+
+        three_by_two_ppm()
+            .write(&mut out, PpmFormat::Ascii)
+            .unwrap();
+
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            cmp
+        );
+    }
+
+    #[test]
+    fn read_ascii() {
+        let file = std::fs::File::open("./tests/three_by_two.ppm").unwrap();
+        let ppm = PPM::read(file).unwrap();
+
+        assert!(ppm == three_by_two_ppm());
+    }
+
+    #[test]
+    fn read_binary_round_trip() {
+        let mut out = Vec::new();
+        three_by_two_ppm()
+            .write(&mut out, PpmFormat::Binary)
+            .unwrap();
+
+        assert!(PPM::parse(&out).unwrap() == three_by_two_ppm());
+    }
+
+    #[test]
+    fn read_comments_and_maxval() {
+        let ppm = PPM::parse(
+            b"P3 # comment\n# another comment\n2 1\n15\n15 0 5\n0 15 15\n",
+        )
+        .unwrap();
+
+        assert_eq!(
+            ppm.get(0, 0).unwrap().to_string(),
+            "255 0 85"
+        );
+        assert_eq!(
+            ppm.get(1, 0).unwrap().to_string(),
+            "0 255 255"
+        );
+    }
+
+    #[test]
+    fn read_binary_sixteen_bit() {
+        let mut bytes = b"P6\n1 1\n65535\n".to_vec();
+        bytes.extend_from_slice(&[255, 255, 0, 0, 128, 0]);
+
+        let ppm = PPM::parse(&bytes).unwrap();
+        assert_eq!(
+            ppm.get(0, 0).unwrap().to_string(),
+            "255 0 128"
+        );
+    }
+
+    #[test]
+    fn read_unsupported_format() {
+        assert!(matches!(
+            PPM::parse(b"P5\n1 1\n255\n\0"),
+            Err(PpmError::UnsupportedFormat(magic)) if magic == "P5"
+        ));
+    }
+
+    #[test]
+    fn read_invalid_header() {
+        assert!(matches!(
+            PPM::parse(b"P3\n2 x\n255\n"),
+            Err(PpmError::InvalidHeader(_))
+        ));
+        assert!(matches!(
+            PPM::parse(b"P3\n2 1\n"),
+            Err(PpmError::InvalidHeader(_))
+        ));
+        assert!(matches!(
+            PPM::parse(b"P3\n2 1\n0\n"),
+            Err(PpmError::InvalidHeader(_))
+        ));
+        assert!(matches!(
+            PPM::parse(b"P6\n3074457345618258603 1\n65535\n"),
+            Err(PpmError::InvalidHeader(_))
+        ));
+    }
+
+    #[test]
+    fn read_invalid_sample() {
+        assert!(matches!(
+            PPM::parse(b"P3\n1 1\n255\n1 256 3\n"),
+            Err(PpmError::InvalidSample(sample)) if sample == "256"
+        ));
+        assert!(matches!(
+            PPM::parse(b"P6\n1 1\n100\n\x01\x65\x03"),
+            Err(PpmError::InvalidSample(sample)) if sample == "101"
+        ));
+        assert!(matches!(
+            PPM::parse(b"P6\n1 1\n1000\n\x00\x01\x03\xe9\x00\x03"),
+            Err(PpmError::InvalidSample(sample)) if sample == "1001"
+        ));
+    }
+
+    #[test]
+    fn read_truncated() {
+        assert!(matches!(
+            PPM::parse(b"P3\n2 1\n255\n1 2 3 4\n"),
+            Err(PpmError::Truncated {
+                expected: 6,
+                found: 4
+            })
+        ));
+        assert!(matches!(
+            PPM::parse(b"P6\n2 1\n255\n\x01\x02\x03"),
+            Err(PpmError::Truncated {
+                expected: 6,
+                found: 3
+            })
+        ));
+    }
+
+    #[test]
+    fn write_binary() {
+        let mut out = Vec::new();
+
+        three_by_two_ppm()
+            .write(&mut out, PpmFormat::Binary)
+            .unwrap();
+
+        let mut cmp = b"P6\n3 2\n255\n".to_vec();
+        cmp.extend_from_slice(&[
+            255, 0, 0, 0, 255, 0, 0, 0, 255, 255, 255, 0, 255, 255, 255, 0, 0,
+            0,
+        ]);
+        assert_eq!(out, cmp);
+    }
 }
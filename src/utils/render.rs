@@ -0,0 +1,607 @@
+use indicatif::ProgressBar;
+use std::sync::Mutex;
+use std::thread;
+
+use super::camera::Camera;
+use super::hittable::*;
+use super::image::HdrImage;
+use super::world::World;
+use crate::math::random::Rng;
+use crate::math::ray::Ray;
+use crate::math::vector3::Vector3;
+
+/// Radiance returned by rays that escape the world.
+#[derive(Clone, Copy, Default)]
+pub enum Background {
+    /// White to light blue gradient along the y axis.
+    #[default]
+    Sky,
+    /// A single color, use black for scenes lit only by emitters.
+    Solid(Vector3),
+}
+
+impl Background {
+    pub fn color(&self, ray: &Ray) -> Vector3 {
+        match self {
+            Background::Sky => {
+                let mut direction = ray.direction;
+                let t = 0.5 * (direction.unit().y + 1.0);
+                Vector3::lerp(
+                    Vector3::new(1.0, 1.0, 1.0),
+                    Vector3::new(0.5, 0.7, 1.0),
+                    t,
+                )
+            }
+            Background::Solid(color) => *color,
+        }
+    }
+}
+
+/// Settings used to render a `World` into an `HdrImage`.
+pub struct Renderer {
+    pub image_width: u64,
+    pub image_height: u64,
+    /// Number of jittered rays averaged for every pixel.
+    pub samples_per_pixel: u32,
+    /// Maximum number of bounces for a single ray.
+    pub max_depth: u32,
+    pub background: Background,
+    /// Seed every pixel's random stream is derived from.
+    pub seed: u64,
+    /// Number of threads used to render the image.
+    pub threads: usize,
+}
+
+impl Renderer {
+    /// Creates a renderer with 100 samples per pixel, a maximum depth of
+    /// 50, a sky background, a seed of 0 and one thread per core.
+    pub fn new(image_width: u64, image_height: u64) -> Self {
+        Self {
+            image_width,
+            image_height,
+            samples_per_pixel: 100,
+            max_depth: 50,
+            background: Background::Sky,
+            seed: 0,
+            threads: thread::available_parallelism()
+                .map(|threads| threads.get())
+                .unwrap_or(1),
+        }
+    }
+
+    /// Renders the world as seen by the camera, advancing `bar` once for
+    /// every pixel.
+    ///
+    /// Rows are handed out to `threads` worker threads as they become
+    /// free. Every pixel draws from its own random stream, so the same seed
+    /// always gives the same image regardless of the thread count. An empty
+    /// image is returned if either dimension is zero, and at least one
+    /// sample is taken per pixel.
+    pub fn render(
+        &self,
+        world: &World,
+        camera: &Camera,
+        bar: &ProgressBar,
+    ) -> HdrImage {
+        let mut image = HdrImage::new(self.image_width, self.image_height);
+        if image.pixels.is_empty() {
+            return image;
+        }
+        let width = self.image_width as usize;
+
+        let rows = Mutex::new(image.pixels.chunks_mut(width).enumerate());
+        let next_row = || rows.lock().unwrap().next();
+
+        thread::scope(|scope| {
+            for _ in 0..self.threads.max(1) {
+                scope.spawn(|| {
+                    while let Some((row, pixels)) = next_row() {
+                        for (column, pixel) in pixels.iter_mut().enumerate() {
+                            *pixel = self.render_pixel(
+                                world,
+                                camera,
+                                column as u64,
+                                row as u64,
+                            );
+                        }
+
+                        bar.inc(self.image_width);
+                    }
+                });
+            }
+        });
+
+        image
+    }
+
+    /// Averages the samples for the pixel at `column` and `row`, counted
+    /// from the top left of the image.
+    ///
+    /// A single sample goes through the pixel center, more samples are
+    /// jittered randomly inside the pixel.
+    fn render_pixel(
+        &self,
+        world: &World,
+        camera: &Camera,
+        column: u64,
+        row: u64,
+    ) -> Vector3 {
+        let mut rng = Rng::from_stream(
+            self.seed,
+            row * self.image_width + column,
+        );
+
+        // Viewport coordinates start at the bottom left.
+        let i = column as f64;
+        let j = (self.image_height - row - 1) as f64;
+
+        let samples = self.samples_per_pixel.max(1);
+        let mut color = Vector3::new(0.0, 0.0, 0.0);
+        for _ in 0..samples {
+            let (du, dv) = if samples == 1 {
+                (0.0, 0.0)
+            } else {
+                (
+                    rng.next_f64() - 0.5,
+                    rng.next_f64() - 0.5,
+                )
+            };
+
+            let u = viewport_coordinate(i + du, self.image_width);
+            let v = viewport_coordinate(j + dv, self.image_height);
+            let r = camera.get_ray(u, v, &mut rng);
+
+            color = color
+                + ray_color(
+                    &r,
+                    world,
+                    self.background,
+                    self.max_depth,
+                    &mut rng,
+                );
+        }
+
+        color / samples as f64
+    }
+}
+
+/// Maps a pixel coordinate along a dimension of `size` pixels to `[0, 1]`,
+/// the first and last pixels sit on the edges of the viewport. A single
+/// pixel looks through the center instead of dividing by zero.
+fn viewport_coordinate(pixel: f64, size: u64) -> f64 {
+    if size > 1 {
+        pixel / (size - 1) as f64
+    } else {
+        pixel + 0.5
+    }
+}
+
+/// Image size and sampling settings that may be left unspecified, to be
+/// filled in from another source or the defaults.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct RenderSettings {
+    pub width: Option<u64>,
+    pub height: Option<u64>,
+    pub samples_per_pixel: Option<u32>,
+    pub max_depth: Option<u32>,
+    pub seed: Option<u64>,
+}
+
+impl RenderSettings {
+    pub const DEFAULT_WIDTH: u64 = 400;
+    pub const DEFAULT_ASPECT_RATIO: f64 = 16.0 / 9.0;
+
+    /// Returns these settings with every unspecified field taken from
+    /// `other`.
+    ///
+    /// The size is taken as a whole, so that giving only a width doesn't
+    /// stretch the image to the height of `other`.
+    pub fn or(self, other: Self) -> Self {
+        let (width, height) = if self.width.is_some() || self.height.is_some()
+        {
+            (self.width, self.height)
+        } else {
+            (other.width, other.height)
+        };
+
+        Self {
+            width,
+            height,
+            samples_per_pixel: self
+                .samples_per_pixel
+                .or(other.samples_per_pixel),
+            max_depth: self.max_depth.or(other.max_depth),
+            seed: self.seed.or(other.seed),
+        }
+    }
+
+    /// Returns the image width and height.
+    ///
+    /// A missing dimension is derived from the other one at 16:9, the width
+    /// defaults to 400 pixels.
+    pub fn size(&self) -> (u64, u64) {
+        let scaled = |pixels: u64, factor: f64| {
+            ((pixels as f64 * factor) as u64).max(1)
+        };
+
+        match (self.width, self.height) {
+            (Some(width), Some(height)) => (width, height),
+            (Some(width), None) => (
+                width,
+                scaled(
+                    width,
+                    1.0 / Self::DEFAULT_ASPECT_RATIO,
+                ),
+            ),
+            (None, Some(height)) => (
+                scaled(height, Self::DEFAULT_ASPECT_RATIO),
+                height,
+            ),
+            (None, None) => (
+                Self::DEFAULT_WIDTH,
+                scaled(
+                    Self::DEFAULT_WIDTH,
+                    1.0 / Self::DEFAULT_ASPECT_RATIO,
+                ),
+            ),
+        }
+    }
+
+    /// Creates a renderer using the specified settings and the defaults of
+    /// `Renderer::new` for the rest.
+    pub fn renderer(&self) -> Renderer {
+        let (width, height) = self.size();
+        let mut renderer = Renderer::new(width, height);
+
+        if let Some(samples_per_pixel) = self.samples_per_pixel {
+            renderer.samples_per_pixel = samples_per_pixel;
+        }
+        if let Some(max_depth) = self.max_depth {
+            renderer.max_depth = max_depth;
+        }
+        if let Some(seed) = self.seed {
+            renderer.seed = seed;
+        }
+
+        renderer
+    }
+}
+
+/// Traces `ray` through the world, bouncing at most `depth` times.
+pub fn ray_color(
+    ray: &Ray,
+    world: &World,
+    background: Background,
+    depth: u32,
+    rng: &mut Rng,
+) -> Vector3 {
+    if depth == 0 {
+        return Vector3::new(0.0, 0.0, 0.0);
+    }
+
+    // Ignore hits very close to zero to avoid shadow acne.
+    let hit = match world.hit(ray, 0.001..=f64::MAX) {
+        Some(hit) => hit,
+        None => return background.color(ray),
+    };
+
+    let emitted = hit.material.emitted(&hit);
+    match hit.material.scatter(ray, &hit, rng) {
+        Some((attenuation, scattered)) => {
+            emitted
+                + attenuation
+                    * ray_color(
+                        &scattered,
+                        world,
+                        background,
+                        depth - 1,
+                        rng,
+                    )
+        }
+        None => emitted,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::color::Transfer;
+    use crate::utils::material::DiffuseLight;
+    use crate::utils::material::Lambertian;
+    use crate::utils::sphere::Sphere;
+    use std::sync::Arc;
+
+    fn lit_world() -> World {
+        let mut world = World::default();
+        world.add(Box::new(Sphere::new(
+            Vector3::new(0.0, 0.0, -1.0),
+            0.5,
+            Arc::new(DiffuseLight::new(Vector3::new(
+                4.0, 2.0, 1.0,
+            ))),
+        )));
+        world
+    }
+
+    #[test]
+    fn ray_hitting_light() {
+        let ray = Ray::new(
+            Vector3::new(0.0, 0.0, 0.0),
+            Vector3::new(0.0, 0.0, -1.0),
+        );
+        let black = Background::Solid(Vector3::new(0.0, 0.0, 0.0));
+
+        assert_eq!(
+            ray_color(
+                &ray,
+                &lit_world(),
+                black,
+                50,
+                &mut Rng::default()
+            ),
+            Vector3::new(4.0, 2.0, 1.0)
+        );
+    }
+
+    #[test]
+    fn ray_missing_everything() {
+        let ray = Ray::new(
+            Vector3::new(0.0, 0.0, 0.0),
+            Vector3::new(0.0, 0.0, 1.0),
+        );
+        let black = Background::Solid(Vector3::new(0.0, 0.0, 0.0));
+
+        assert_eq!(
+            ray_color(
+                &ray,
+                &lit_world(),
+                black,
+                50,
+                &mut Rng::default()
+            ),
+            Vector3::new(0.0, 0.0, 0.0)
+        );
+    }
+
+    #[test]
+    fn samples_are_averaged() {
+        let mut renderer = Renderer::new(4, 3);
+        renderer.samples_per_pixel = 16;
+        renderer.background = Background::Solid(Vector3::new(0.5, 0.5, 0.5));
+        let camera = Camera::new(
+            2.0,
+            2.0,
+            1.0,
+            Vector3::new(0.0, 0.0, 0.0),
+        );
+
+        let image = renderer.render(
+            &World::default(),
+            &camera,
+            &ProgressBar::hidden(),
+        );
+
+        for pixel in image.pixels.iter() {
+            assert!((*pixel - Vector3::new(0.5, 0.5, 0.5)).near_zero());
+        }
+    }
+
+    fn noisy_render(seed: u64, threads: usize) -> String {
+        let mut world = World::default();
+        world.add(Box::new(Sphere::new(
+            Vector3::new(0.0, 0.0, -1.0),
+            0.5,
+            Arc::new(Lambertian::new(Vector3::new(
+                0.5, 0.5, 0.5,
+            ))),
+        )));
+
+        let mut renderer = Renderer::new(8, 6);
+        renderer.samples_per_pixel = 4;
+        renderer.seed = seed;
+        renderer.threads = threads;
+        let camera = Camera::new(
+            2.0,
+            2.0,
+            1.0,
+            Vector3::new(0.0, 0.0, 0.0),
+        );
+
+        renderer
+            .render(
+                &world,
+                &camera,
+                &ProgressBar::hidden(),
+            )
+            .to_ppm(Transfer::Linear)
+            .to_string()
+    }
+
+    #[test]
+    fn same_seed_same_image() {
+        assert_eq!(
+            noisy_render(3, 1),
+            noisy_render(3, 1)
+        );
+    }
+
+    #[test]
+    fn different_seed_different_image() {
+        assert_ne!(
+            noisy_render(3, 1),
+            noisy_render(4, 1)
+        );
+    }
+
+    #[test]
+    fn thread_count_does_not_change_image() {
+        let single = noisy_render(3, 1);
+
+        for threads in 2..=5 {
+            assert_eq!(noisy_render(3, threads), single);
+        }
+    }
+
+    #[test]
+    fn single_pixel_dimensions() {
+        let camera = Camera::new(
+            2.0,
+            2.0,
+            1.0,
+            Vector3::new(0.0, 0.0, 0.0),
+        );
+        let render = |width, height| {
+            let mut renderer = Renderer::new(width, height);
+            renderer.samples_per_pixel = 1;
+            renderer.render(
+                &lit_world(),
+                &camera,
+                &ProgressBar::hidden(),
+            )
+        };
+
+        // A single column or row looks through the middle of the viewport,
+        // like the middle column or row of a 3 by 3 image.
+        let square = render(3, 3);
+        let column = render(1, 3);
+        let row = render(3, 1);
+        for k in 0..3 {
+            assert_eq!(column.get(0, k), square.get(1, k));
+            assert_eq!(row.get(k, 0), square.get(k, 1));
+        }
+        assert_eq!(
+            render(1, 1).get(0, 0),
+            Some(&Vector3::new(4.0, 2.0, 1.0))
+        );
+    }
+
+    #[test]
+    fn degenerate_renderer() {
+        let camera = Camera::new(
+            2.0,
+            2.0,
+            1.0,
+            Vector3::new(0.0, 0.0, 0.0),
+        );
+        let render = |renderer: &Renderer| {
+            renderer.render(
+                &lit_world(),
+                &camera,
+                &ProgressBar::hidden(),
+            )
+        };
+
+        for (width, height) in [(0, 3), (3, 0), (0, 0)] {
+            let image = render(&Renderer::new(width, height));
+            assert!(image.pixels.is_empty());
+        }
+
+        let mut renderer = Renderer::new(3, 3);
+        renderer.samples_per_pixel = 1;
+        let single = render(&renderer);
+        renderer.samples_per_pixel = 0;
+        assert_eq!(
+            render(&renderer).pixels,
+            single.pixels
+        );
+    }
+
+    #[test]
+    fn progress_counts_every_pixel() {
+        let mut renderer = Renderer::new(7, 5);
+        renderer.samples_per_pixel = 1;
+        renderer.threads = 3;
+        let camera = Camera::new(
+            2.0,
+            2.0,
+            1.0,
+            Vector3::new(0.0, 0.0, 0.0),
+        );
+        let bar = ProgressBar::hidden();
+
+        renderer.render(&World::default(), &camera, &bar);
+
+        assert_eq!(bar.position(), 35);
+    }
+
+    #[test]
+    fn settings_fill_missing_fields() {
+        let cli = RenderSettings {
+            width: Some(64),
+            seed: Some(3),
+            ..Default::default()
+        };
+        let file = RenderSettings {
+            width: Some(32),
+            height: Some(32),
+            samples_per_pixel: Some(8),
+            ..Default::default()
+        };
+
+        assert_eq!(
+            cli.or(file),
+            RenderSettings {
+                width: Some(64),
+                height: None,
+                samples_per_pixel: Some(8),
+                max_depth: None,
+                seed: Some(3),
+            }
+        );
+        assert_eq!(
+            RenderSettings::default().or(file).size(),
+            (32, 32)
+        );
+    }
+
+    #[test]
+    fn settings_size() {
+        let size = |width, height| {
+            RenderSettings {
+                width,
+                height,
+                ..Default::default()
+            }
+            .size()
+        };
+
+        assert_eq!(size(None, None), (400, 225));
+        assert_eq!(size(Some(160), None), (160, 90));
+        assert_eq!(size(None, Some(90)), (160, 90));
+        assert_eq!(size(Some(3), Some(5)), (3, 5));
+    }
+
+    #[test]
+    fn settings_renderer() {
+        let renderer = RenderSettings {
+            samples_per_pixel: Some(4),
+            max_depth: Some(2),
+            ..Default::default()
+        }
+        .renderer();
+
+        assert_eq!(renderer.image_width, 400);
+        assert_eq!(renderer.samples_per_pixel, 4);
+        assert_eq!(renderer.max_depth, 2);
+        assert_eq!(renderer.seed, 0);
+    }
+
+    #[test]
+    fn zero_depth_is_black() {
+        let ray = Ray::new(
+            Vector3::new(0.0, 0.0, 0.0),
+            Vector3::new(0.0, 0.0, -1.0),
+        );
+
+        assert_eq!(
+            ray_color(
+                &ray,
+                &lit_world(),
+                Background::Sky,
+                0,
+                &mut Rng::default()
+            ),
+            Vector3::new(0.0, 0.0, 0.0)
+        );
+    }
+}
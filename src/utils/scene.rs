@@ -0,0 +1,249 @@
+use std::sync::Arc;
+
+use super::camera::CameraSettings;
+use super::material::Dielectric;
+use super::material::DiffuseLight;
+use super::material::Lambertian;
+use super::material::Material;
+use super::material::Metal;
+use super::render::Background;
+use super::render::RenderSettings;
+use super::sphere::Sphere;
+use super::world::World;
+use crate::math::random::Rng;
+use crate::math::vector3::Vector3;
+
+/// Names of the scenes accepted by `Scene::builtin`.
+pub const BUILTIN_SCENES: [&str; 3] = ["spheres", "lights", "random"];
+
+/// Everything needed to render an image.
+#[derive(Default)]
+pub struct Scene {
+    pub world: World,
+    pub camera: CameraSettings,
+    pub background: Background,
+    /// Render settings chosen by the scene, the rest are left to the
+    /// caller.
+    pub settings: RenderSettings,
+}
+
+impl Scene {
+    /// Builds the scene called `name`, returns `None` if there is no such
+    /// scene.
+    pub fn builtin(name: &str) -> Option<Self> {
+        match name {
+            "spheres" => Some(Self::spheres()),
+            "lights" => Some(Self::lights()),
+            "random" => Some(Self::random()),
+            _ => None,
+        }
+    }
+
+    /// Diffuse, hollow glass and metal spheres on a large ground sphere.
+    pub fn spheres() -> Self {
+        let mut world = World::default();
+        world.add(Box::new(Sphere::new(
+            Vector3::new(0.0, 0.0, -1.0),
+            0.5,
+            Arc::new(Lambertian::new(Vector3::new(
+                0.5, 0.5, 0.5,
+            ))),
+        )));
+        // Hollow glass sphere, the negative radius flips the inner normals.
+        let glass = Arc::new(Dielectric::new(1.5));
+        world.add(Box::new(Sphere::new(
+            Vector3::new(-1.0, 0.0, -1.0),
+            0.5,
+            glass.clone(),
+        )));
+        world.add(Box::new(Sphere::new(
+            Vector3::new(-1.0, 0.0, -1.0),
+            -0.4,
+            glass,
+        )));
+        world.add(Box::new(Sphere::new(
+            Vector3::new(1.0, 0.0, -1.0),
+            0.5,
+            Arc::new(Metal::new(
+                Vector3::new(0.8, 0.6, 0.2),
+                0.0,
+            )),
+        )));
+        world.add(Box::new(ground()));
+
+        let camera = CameraSettings {
+            look_from: Vector3::new(-2.0, 2.0, 1.0),
+            look_at: Vector3::new(0.0, 0.0, -1.0),
+            vertical_fov: 20.0,
+            aperture: 0.1,
+            ..Default::default()
+        };
+
+        Self {
+            world,
+            camera,
+            ..Default::default()
+        }
+    }
+
+    /// A diffuse sphere lit by a bright emitter in an otherwise black
+    /// world, useful to try out tone mapping.
+    pub fn lights() -> Self {
+        let mut world = World::default();
+        world.add(Box::new(Sphere::new(
+            Vector3::new(0.0, 0.0, -1.0),
+            0.5,
+            Arc::new(Lambertian::new(Vector3::new(
+                0.7, 0.3, 0.3,
+            ))),
+        )));
+        world.add(Box::new(Sphere::new(
+            Vector3::new(1.0, 1.2, -0.5),
+            0.3,
+            Arc::new(DiffuseLight::new(Vector3::new(
+                20.0, 18.0, 15.0,
+            ))),
+        )));
+        world.add(Box::new(ground()));
+
+        let camera = CameraSettings {
+            look_from: Vector3::new(0.0, 0.5, 2.0),
+            look_at: Vector3::new(0.0, 0.0, -1.0),
+            vertical_fov: 40.0,
+            ..Default::default()
+        };
+
+        Self {
+            world,
+            camera,
+            background: Background::Solid(Vector3::new(0.0, 0.0, 0.0)),
+            ..Default::default()
+        }
+    }
+
+    /// A grid of small randomly placed spheres around three large ones.
+    ///
+    /// The layout is always the same, it doesn't depend on the render seed.
+    pub fn random() -> Self {
+        let mut rng = Rng::new(0);
+        let mut world = World::default();
+
+        world.add(Box::new(Sphere::new(
+            Vector3::new(0.0, -1000.0, 0.0),
+            1000.0,
+            Arc::new(Lambertian::new(Vector3::new(
+                0.5, 0.5, 0.5,
+            ))),
+        )));
+
+        for a in -11..11 {
+            for b in -11..11 {
+                let center = Vector3::new(
+                    a as f64 + 0.9 * rng.next_f64(),
+                    0.2,
+                    b as f64 + 0.9 * rng.next_f64(),
+                );
+                if (center - Vector3::new(4.0, 0.2, 0.0)).magnitude() <= 0.9 {
+                    continue;
+                }
+
+                let choice = rng.next_f64();
+                let material: Arc<dyn Material> = if choice < 0.8 {
+                    let albedo = random_color(&mut rng, 0.0, 1.0)
+                        * random_color(&mut rng, 0.0, 1.0);
+                    Arc::new(Lambertian::new(albedo))
+                } else if choice < 0.95 {
+                    Arc::new(Metal::new(
+                        random_color(&mut rng, 0.5, 1.0),
+                        rng.range(0.0, 0.5),
+                    ))
+                } else {
+                    Arc::new(Dielectric::new(1.5))
+                };
+
+                world.add(Box::new(Sphere::new(
+                    center, 0.2, material,
+                )));
+            }
+        }
+
+        world.add(Box::new(Sphere::new(
+            Vector3::new(0.0, 1.0, 0.0),
+            1.0,
+            Arc::new(Dielectric::new(1.5)),
+        )));
+        world.add(Box::new(Sphere::new(
+            Vector3::new(-4.0, 1.0, 0.0),
+            1.0,
+            Arc::new(Lambertian::new(Vector3::new(
+                0.4, 0.2, 0.1,
+            ))),
+        )));
+        world.add(Box::new(Sphere::new(
+            Vector3::new(4.0, 1.0, 0.0),
+            1.0,
+            Arc::new(Metal::new(
+                Vector3::new(0.7, 0.6, 0.5),
+                0.0,
+            )),
+        )));
+
+        let camera = CameraSettings {
+            look_from: Vector3::new(13.0, 2.0, 3.0),
+            look_at: Vector3::new(0.0, 0.0, 0.0),
+            vertical_fov: 20.0,
+            aperture: 0.1,
+            focus_distance: Some(10.0),
+            ..Default::default()
+        };
+
+        Self {
+            world,
+            camera,
+            ..Default::default()
+        }
+    }
+}
+
+fn ground() -> Sphere {
+    Sphere::new(
+        Vector3::new(0.0, -100.5, -1.0),
+        100.0,
+        Arc::new(Lambertian::new(Vector3::new(
+            0.5, 0.5, 0.5,
+        ))),
+    )
+}
+
+fn random_color(rng: &mut Rng, min: f64, max: f64) -> Vector3 {
+    Vector3::new(
+        rng.range(min, max),
+        rng.range(min, max),
+        rng.range(min, max),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::hittable::Hittable;
+
+    #[test]
+    fn every_builtin_scene_exists() {
+        for name in BUILTIN_SCENES {
+            assert!(Scene::builtin(name).is_some());
+        }
+        assert!(Scene::builtin("missing").is_none());
+    }
+
+    #[test]
+    fn camera_sees_the_scene() {
+        for name in BUILTIN_SCENES {
+            let scene = Scene::builtin(name).unwrap();
+            let mut rng = Rng::default();
+            let ray = scene.camera.build(1.0).get_ray(0.5, 0.5, &mut rng);
+
+            assert!(scene.world.hit(&ray, 0.001..=f64::MAX).is_some());
+        }
+    }
+}
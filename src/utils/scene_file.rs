@@ -0,0 +1,983 @@
+//! Plain text scene descriptions.
+//!
+//! Every line holds one statement, `#` starts a comment running to the end
+//! of the line. Vectors and colors are written as three numbers.
+//!
+//! ```text
+//! render width 400 height 225 samples 100 max_depth 50 seed 0
+//! background sky
+//! camera from -2 2 1 at 0 0 -1 up 0 1 0 fov 20 aperture 0.1
+//!
+//! material ground lambertian 0.5 0.5 0.5
+//! material gold metal 0.8 0.6 0.2 0.1
+//! material glass dielectric 1.5
+//! material lamp light 4 4 4
+//!
+//! sphere 0 -100.5 -1 100 ground
+//! sphere 1 0 -1 0.5 gold
+//! ```
+//!
+//! - `render` takes any of `width`, `height`, `samples` and `max_depth`,
+//!   each followed by a positive integer, and a `seed`. The settings may
+//!   be split over several lines, but each is given only once.
+//! - `background` is either `sky` or a color.
+//! - `camera` takes `from` and `at` points, an `up` vector, the vertical
+//!   `fov` in degrees between 0 and 180, the lens `aperture` and the
+//!   positive `focus` distance, which defaults to the distance between
+//!   `from` and `at`.
+//! - `material NAME TYPE ...` defines a `lambertian` or `light` color, a
+//!   `metal` color with an optional fuzz, or a `dielectric` with a positive
+//!   index of refraction. Materials must be defined before they are used.
+//! - `sphere X Y Z RADIUS MATERIAL` adds a sphere, a negative radius makes
+//!   it hollow and a zero radius is an error.
+//! - `triangle A B C MATERIAL` adds a triangle from three points, facing the
+//!   side they appear counter clockwise from. It may be followed by
+//!   `normals` and three vectors for smooth shading, and by `cull` to make
+//!   it invisible from behind.
+//! - `mesh PATH MATERIAL` adds a Wavefront OBJ model. Faces keep the
+//!   materials of its MTL library, faces before the first `usemtl` use
+//!   `MATERIAL`. Relative paths start from the directory of the scene
+//!   file.
+
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::Read;
+use std::path::Path;
+use std::str::FromStr;
+use std::sync::Arc;
+
+use super::camera::CameraSettings;
+use super::json::JsonError;
+use super::material::Dielectric;
+use super::material::DiffuseLight;
+use super::material::Lambertian;
+use super::material::Material;
+use super::material::Metal;
+use super::obj::load_obj;
+use super::render::Background;
+use super::render::RenderSettings;
+use super::scene::Scene;
+use super::sphere::Sphere;
+use super::triangle::Triangle;
+use crate::math::vector3::Vector3;
+
+/// Error returned when a scene file can't be loaded.
+#[derive(Debug)]
+pub enum SceneError {
+    Io(std::io::Error),
+    /// The file is not valid UTF-8.
+    InvalidText,
+    /// A problem at the 1 based `line` and `column`.
+    Syntax {
+        line: usize,
+        column: usize,
+        message: String,
+    },
+    /// Well formed JSON that doesn't describe a scene.
+    Invalid(String),
+}
+
+impl std::fmt::Display for SceneError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            SceneError::Io(error) => write!(f, "{}", error),
+            SceneError::InvalidText => write!(f, "scene is not valid UTF-8"),
+            SceneError::Invalid(message) => write!(f, "{}", message),
+            SceneError::Syntax {
+                line,
+                column,
+                message,
+            } => write!(
+                f,
+                "{}:{}: {}",
+                line, column, message
+            ),
+        }
+    }
+}
+
+impl std::error::Error for SceneError {}
+
+impl From<std::io::Error> for SceneError {
+    fn from(error: std::io::Error) -> Self {
+        SceneError::Io(error)
+    }
+}
+
+impl From<JsonError> for SceneError {
+    fn from(error: JsonError) -> Self {
+        SceneError::Syntax {
+            line: error.line,
+            column: error.column,
+            message: error.message,
+        }
+    }
+}
+
+impl Scene {
+    /// Loads the scene at `path`, as JSON if it has a `.json` extension and
+    /// as text otherwise.
+    pub fn open(path: &Path) -> Result<Self, SceneError> {
+        let file = File::open(path)?;
+
+        match path.extension().and_then(|extension| extension.to_str()) {
+            Some(extension) if extension.eq_ignore_ascii_case("json") => {
+                Self::read_json(file)
+            }
+            _ => Self::parse_in(
+                &read_text(file)?,
+                path.parent().unwrap_or_else(|| Path::new("")),
+            ),
+        }
+    }
+
+    /// Reads the entire scene from `reader`.
+    pub fn read<R: Read>(reader: R) -> Result<Self, SceneError> {
+        Self::parse(&read_text(reader)?)
+    }
+
+    /// Parses a scene description, see the module documentation for the
+    /// format. Meshes are loaded relative to the working directory.
+    pub fn parse(source: &str) -> Result<Self, SceneError> {
+        Self::parse_in(source, Path::new(""))
+    }
+
+    /// Parses a scene description, loading meshes relative to `directory`.
+    fn parse_in(source: &str, directory: &Path) -> Result<Self, SceneError> {
+        let mut scene = Self::default();
+        let mut materials: HashMap<&str, Arc<dyn Material>> = HashMap::new();
+        let mut camera_seen = false;
+
+        for (index, text) in source.lines().enumerate() {
+            let mut line = Line::new(index + 1, text);
+            let (column, statement) = match line.next_token() {
+                Some(token) => token,
+                None => continue,
+            };
+
+            match statement {
+                "render" => line.render_settings(&mut scene.settings)?,
+                "background" => {
+                    scene.background = if line.peek() == Some("sky") {
+                        line.next_token();
+                        Background::Sky
+                    } else {
+                        Background::Solid(line.color()?)
+                    }
+                }
+                "camera" => {
+                    if camera_seen {
+                        return Err(
+                            line.error(column, "camera is defined twice")
+                        );
+                    }
+                    camera_seen = true;
+                    let camera = line.camera_settings()?;
+                    camera
+                        .validate()
+                        .map_err(|message| line.error(column, message))?;
+                    scene.camera = camera;
+                }
+                "material" => {
+                    let (column, name) = line.expect("a material name")?;
+                    if materials.contains_key(name) {
+                        return Err(line.error(
+                            column,
+                            format!(
+                                "material `{}` is defined twice",
+                                name
+                            ),
+                        ));
+                    }
+                    materials.insert(name, line.material()?);
+                }
+                "sphere" => {
+                    let position = line.vector()?;
+                    let radius = line
+                        .number_where("a non-zero radius", |radius| {
+                            radius != 0.0
+                        })?;
+                    let material = line.material_name(&materials)?;
+
+                    scene.world.add(Box::new(Sphere::new(
+                        position, radius, material,
+                    )));
+                }
+                "triangle" => {
+                    let a = line.vector()?;
+                    let b = line.vector()?;
+                    let c = line.vector()?;
+                    let material = line.material_name(&materials)?;
+                    let mut triangle = Triangle::new(a, b, c, material);
+
+                    while let Some((column, key)) = line.next_token() {
+                        match key {
+                            "normals" => {
+                                triangle.normals = Some([
+                                    line.vector()?,
+                                    line.vector()?,
+                                    line.vector()?,
+                                ])
+                            }
+                            "cull" => triangle.cull_back_faces = true,
+                            _ => {
+                                return Err(line.error(
+                                    column,
+                                    format!(
+                                        "unknown triangle setting `{}`",
+                                        key
+                                    ),
+                                ))
+                            }
+                        }
+                    }
+
+                    scene.world.add(Box::new(triangle));
+                }
+                "mesh" => {
+                    let (column, path) = line.expect("a path")?;
+                    let material = line.material_name(&materials)?;
+                    let path = directory.join(path);
+                    let mesh = load_obj(&path, material).map_err(|error| {
+                        line.error(
+                            column,
+                            format!("{}: {}", path.display(), error),
+                        )
+                    })?;
+
+                    scene.world.add(Box::new(mesh));
+                }
+                _ => {
+                    return Err(line.error(
+                        column,
+                        format!("unknown statement `{}`", statement),
+                    ))
+                }
+            }
+
+            line.finish()?;
+        }
+
+        Ok(scene)
+    }
+}
+
+/// Reads all of `reader` as UTF-8 text.
+pub(crate) fn read_text<R: Read>(mut reader: R) -> Result<String, SceneError> {
+    let mut bytes = Vec::new();
+    reader.read_to_end(&mut bytes)?;
+
+    String::from_utf8(bytes).map_err(|_| SceneError::InvalidText)
+}
+
+/// Whitespace separated tokens of a single line, with their 1 based
+/// columns.
+struct Line<'a> {
+    number: usize,
+    tokens: Vec<(usize, &'a str)>,
+    next: usize,
+    /// Column just past the last character, where missing tokens are
+    /// reported.
+    end: usize,
+}
+
+impl<'a> Line<'a> {
+    fn new(number: usize, text: &'a str) -> Self {
+        let text = match text.find('#') {
+            Some(comment) => &text[..comment],
+            None => text,
+        };
+
+        let mut tokens = Vec::new();
+        let mut start = None;
+        for (column, (offset, character)) in text.char_indices().enumerate() {
+            match (character.is_whitespace(), start) {
+                (false, None) => start = Some((column, offset)),
+                (true, Some((start_column, start_offset))) => {
+                    tokens.push((
+                        start_column + 1,
+                        &text[start_offset..offset],
+                    ));
+                    start = None;
+                }
+                _ => {}
+            }
+        }
+        if let Some((start_column, start_offset)) = start {
+            tokens.push((
+                start_column + 1,
+                &text[start_offset..],
+            ));
+        }
+
+        Self {
+            number,
+            tokens,
+            next: 0,
+            end: text.chars().count() + 1,
+        }
+    }
+
+    fn error(&self, column: usize, message: impl Into<String>) -> SceneError {
+        SceneError::Syntax {
+            line: self.number,
+            column,
+            message: message.into(),
+        }
+    }
+
+    fn peek(&self) -> Option<&'a str> {
+        self.tokens.get(self.next).map(|(_, token)| *token)
+    }
+
+    fn next_token(&mut self) -> Option<(usize, &'a str)> {
+        let token = self.tokens.get(self.next).copied();
+        self.next += 1;
+        token
+    }
+
+    /// Returns the next token, or an error saying `what` was expected.
+    fn expect(&mut self, what: &str) -> Result<(usize, &'a str), SceneError> {
+        self.next_token().ok_or_else(|| {
+            self.error(
+                self.end,
+                format!("expected {}", what),
+            )
+        })
+    }
+
+    /// Parses the next token as a finite number.
+    fn number(&mut self, what: &str) -> Result<f64, SceneError> {
+        self.number_where(what, |_| true)
+    }
+
+    /// Parses the next token as a finite number accepted by `valid`.
+    fn number_where(
+        &mut self,
+        what: &str,
+        valid: impl Fn(f64) -> bool,
+    ) -> Result<f64, SceneError> {
+        let (column, token) = self.expect(what)?;
+        token
+            .parse()
+            .ok()
+            .filter(|number: &f64| number.is_finite() && valid(*number))
+            .ok_or_else(|| {
+                self.error(
+                    column,
+                    format!(
+                        "expected {}, found `{}`",
+                        what, token
+                    ),
+                )
+            })
+    }
+
+    /// Parses the next token as an integer greater than zero.
+    fn positive<T>(&mut self, what: &str) -> Result<T, SceneError>
+    where
+        T: FromStr + Default + PartialOrd,
+    {
+        let (column, token) = self.expect(what)?;
+        token
+            .parse()
+            .ok()
+            .filter(|number| *number > T::default())
+            .ok_or_else(|| {
+                self.error(
+                    column,
+                    format!(
+                        "expected {}, found `{}`",
+                        what, token
+                    ),
+                )
+            })
+    }
+
+    fn vector(&mut self) -> Result<Vector3, SceneError> {
+        Ok(Vector3::new(
+            self.number("an x coordinate")?,
+            self.number("a y coordinate")?,
+            self.number("a z coordinate")?,
+        ))
+    }
+
+    fn color(&mut self) -> Result<Vector3, SceneError> {
+        Ok(Vector3::new(
+            self.number("a red value")?,
+            self.number("a green value")?,
+            self.number("a blue value")?,
+        ))
+    }
+
+    /// Adds the settings on the line to `settings`, each of them may only
+    /// be given once per scene.
+    fn render_settings(
+        &mut self,
+        settings: &mut RenderSettings,
+    ) -> Result<(), SceneError> {
+        while let Some((column, key)) = self.next_token() {
+            let given = match key {
+                "width" => settings.width.is_some(),
+                "height" => settings.height.is_some(),
+                "samples" => settings.samples_per_pixel.is_some(),
+                "max_depth" => settings.max_depth.is_some(),
+                "seed" => settings.seed.is_some(),
+                _ => false,
+            };
+            if given {
+                return Err(self.error(
+                    column,
+                    format!(
+                        "render setting `{}` is given twice",
+                        key
+                    ),
+                ));
+            }
+
+            match key {
+                "width" => {
+                    settings.width = Some(self.positive("a positive width")?)
+                }
+                "height" => {
+                    settings.height = Some(self.positive("a positive height")?)
+                }
+                "samples" => {
+                    settings.samples_per_pixel =
+                        Some(self.positive("a positive sample count")?)
+                }
+                "max_depth" => {
+                    settings.max_depth =
+                        Some(self.positive("a positive depth")?)
+                }
+                "seed" => {
+                    let (column, token) = self.expect("a seed")?;
+                    settings.seed = Some(token.parse().map_err(|_| {
+                        self.error(
+                            column,
+                            format!(
+                                "expected a seed, found `{}`",
+                                token
+                            ),
+                        )
+                    })?)
+                }
+                _ => {
+                    return Err(self.error(
+                        column,
+                        format!("unknown render setting `{}`", key),
+                    ))
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    fn camera_settings(&mut self) -> Result<CameraSettings, SceneError> {
+        let mut settings = CameraSettings::default();
+
+        while let Some((column, key)) = self.next_token() {
+            match key {
+                "from" => settings.look_from = self.vector()?,
+                "at" => settings.look_at = self.vector()?,
+                "up" => settings.up = self.vector()?,
+                "fov" => {
+                    settings.vertical_fov = self.number_where(
+                        "a field of view between 0 and 180",
+                        |fov| fov > 0.0 && fov < 180.0,
+                    )?
+                }
+                "aperture" => {
+                    settings.aperture = self.number_where(
+                        "a non-negative aperture",
+                        |aperture| aperture >= 0.0,
+                    )?
+                }
+                "focus" => {
+                    settings.focus_distance = Some(self.number_where(
+                        "a positive focus distance",
+                        |distance| distance > 0.0,
+                    )?)
+                }
+                _ => {
+                    return Err(self.error(
+                        column,
+                        format!("unknown camera setting `{}`", key),
+                    ))
+                }
+            }
+        }
+
+        Ok(settings)
+    }
+
+    fn material(&mut self) -> Result<Arc<dyn Material>, SceneError> {
+        let (column, kind) = self.expect("a material type")?;
+
+        Ok(match kind {
+            "lambertian" => Arc::new(Lambertian::new(self.color()?)),
+            "metal" => {
+                let albedo = self.color()?;
+                let fuzz = match self.peek() {
+                    Some(_) => self.number("a fuzz")?,
+                    None => 0.0,
+                };
+                Arc::new(Metal::new(albedo, fuzz))
+            }
+            "dielectric" => Arc::new(Dielectric::new(
+                self.number_where(
+                    "a positive index of refraction",
+                    |ior| ior > 0.0,
+                )?,
+            )),
+            "light" => Arc::new(DiffuseLight::new(self.color()?)),
+            _ => {
+                return Err(self.error(
+                    column,
+                    format!("unknown material type `{}`", kind),
+                ))
+            }
+        })
+    }
+
+    /// Looks up the material named by the next token.
+    fn material_name(
+        &mut self,
+        materials: &HashMap<&str, Arc<dyn Material>>,
+    ) -> Result<Arc<dyn Material>, SceneError> {
+        let (column, name) = self.expect("a material name")?;
+
+        materials.get(name).cloned().ok_or_else(|| {
+            self.error(
+                column,
+                format!("unknown material `{}`", name),
+            )
+        })
+    }
+
+    /// Fails if any tokens are left.
+    fn finish(&mut self) -> Result<(), SceneError> {
+        match self.next_token() {
+            Some((column, token)) => Err(self.error(
+                column,
+                format!("unexpected `{}`", token),
+            )),
+            None => Ok(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::math::ray::Ray;
+    use crate::utils::hittable::Hittable;
+
+    fn syntax_error(source: &str) -> (usize, usize, String) {
+        match Scene::parse(source) {
+            Err(SceneError::Syntax {
+                line,
+                column,
+                message,
+            }) => (line, column, message),
+            Err(error) => panic!("unexpected error {}", error),
+            Ok(_) => panic!("expected an error"),
+        }
+    }
+
+    #[test]
+    fn parse_full_scene() {
+        let scene = Scene::parse(
+            "# A comment
+            render width 64 height 32 samples 4 max_depth 8 seed 9
+            background 0.1 0.2 0.3
+
+            camera from 0 0 3 at 0 0 0 fov 40 aperture 0.5 focus 2.5
+            material red lambertian 0.8 0.1 0.1 # trailing comment
+            material gold metal 0.8 0.6 0.2 0.3
+            material mirror metal 1 1 1
+            material glass dielectric 1.5
+            material lamp light 4 4 4
+            sphere 0 0 0 1 red
+            sphere 0 0 -5 -0.5 glass
+            ",
+        )
+        .unwrap();
+
+        assert_eq!(
+            scene.settings,
+            RenderSettings {
+                width: Some(64),
+                height: Some(32),
+                samples_per_pixel: Some(4),
+                max_depth: Some(8),
+                seed: Some(9),
+            }
+        );
+        assert!(matches!(
+            scene.background,
+            Background::Solid(color) if color == Vector3::new(0.1, 0.2, 0.3)
+        ));
+        assert_eq!(
+            scene.camera,
+            CameraSettings {
+                look_from: Vector3::new(0.0, 0.0, 3.0),
+                look_at: Vector3::new(0.0, 0.0, 0.0),
+                up: Vector3::new(0.0, 1.0, 0.0),
+                vertical_fov: 40.0,
+                aperture: 0.5,
+                focus_distance: Some(2.5),
+            }
+        );
+
+        let ray = Ray::new(
+            Vector3::new(0.0, 0.0, 3.0),
+            Vector3::new(0.0, 0.0, -1.0),
+        );
+        let hit = scene.world.hit(&ray, 0.0..=f64::MAX).unwrap();
+        assert_eq!(hit.t, 2.0);
+    }
+
+    #[test]
+    fn empty_scene_uses_defaults() {
+        let scene = Scene::parse("\n  # nothing here\n").unwrap();
+
+        assert_eq!(
+            scene.settings,
+            RenderSettings::default()
+        );
+        assert_eq!(
+            scene.camera,
+            CameraSettings::default()
+        );
+        assert!(matches!(
+            scene.background,
+            Background::Sky
+        ));
+    }
+
+    #[test]
+    fn render_settings_combine() {
+        let scene =
+            Scene::parse("render width 64 seed 3\nrender samples 2").unwrap();
+
+        assert_eq!(
+            scene.settings,
+            RenderSettings {
+                width: Some(64),
+                samples_per_pixel: Some(2),
+                seed: Some(3),
+                ..Default::default()
+            }
+        );
+    }
+
+    #[test]
+    fn camera_focuses_on_target_by_default() {
+        let scene =
+            Scene::parse("camera from 0 0 4 at 0 0 0 aperture 1").unwrap();
+        let camera = scene.camera.build(2.0);
+
+        assert_eq!(camera.focus_distance, 4.0);
+        assert_eq!(camera.lens_radius, 0.5);
+    }
+
+    #[test]
+    fn example_scene_parses() {
+        let source = include_str!("../../scenes/spheres.scene");
+        let scene = Scene::parse(source).unwrap();
+
+        let ray = scene.camera.build(16.0 / 9.0).get_ray(
+            0.5,
+            0.5,
+            &mut crate::math::random::Rng::default(),
+        );
+        assert!(scene.world.hit(&ray, 0.001..=f64::MAX).is_some());
+    }
+
+    #[test]
+    fn parse_triangles() {
+        assert_eq!(
+            syntax_error(
+                "material red lambertian 1 0 0
+triangle 0 0 0 1 0 0 0 1 0 red shiny"
+            ),
+            (
+                2,
+                32,
+                "unknown triangle setting `shiny`".to_string()
+            )
+        );
+
+        let scene = Scene::parse(
+            "material red lambertian 1 0 0
+            triangle -1 -1 -1  1 -1 -1  0 1 -1 red cull
+            triangle -1 -1 -2  1 -1 -2  0 1 -2 red normals 0 0 1 0 0 1 0 0 1",
+        )
+        .unwrap();
+
+        // Both triangles face +z, only the first one is culled from behind.
+        let ray = Ray::new(
+            Vector3::new(0.0, 0.0, -3.0),
+            Vector3::new(0.0, 0.0, 1.0),
+        );
+        let hit = scene.world.hit(&ray, 0.0..=f64::MAX).unwrap();
+        assert_eq!(hit.t, 1.0);
+        assert!(!hit.front_face);
+        assert!(hit.barycentric.is_some());
+
+        let ray = Ray::new(
+            Vector3::new(0.0, 0.0, -1.5),
+            Vector3::new(0.0, 0.0, 1.0),
+        );
+        assert!(scene.world.hit(&ray, 0.0..=f64::MAX).is_none());
+    }
+
+    #[test]
+    fn meshes_load_next_to_scene() {
+        let scenes = Path::new(env!("CARGO_MANIFEST_DIR")).join("scenes");
+        let scene = Scene::open(&scenes.join("pyramid.scene")).unwrap();
+
+        // Straight down onto the tip of the pyramid rather than the ground.
+        let ray = Ray::new(
+            Vector3::new(0.0, 1.0, -1.0),
+            Vector3::new(0.0, -1.0, 0.0),
+        );
+        let hit = scene.world.hit(&ray, 0.0..=f64::MAX).unwrap();
+        assert!((hit.t - 0.7).abs() < 1e-9);
+
+        // Missing models are reported at their path.
+        let (line, column, message) = syntax_error(
+            "material red lambertian 1 0 0\nmesh does-not-exist.obj red",
+        );
+        assert_eq!((line, column), (2, 6));
+        assert!(message.starts_with("does-not-exist.obj: "));
+
+        assert_eq!(
+            syntax_error("mesh"),
+            (1, 5, "expected a path".to_string())
+        );
+    }
+
+    #[test]
+    fn unknown_statement() {
+        assert_eq!(
+            syntax_error("\n  cube 1 2 3"),
+            (
+                2,
+                3,
+                "unknown statement `cube`".to_string()
+            )
+        );
+    }
+
+    #[test]
+    fn invalid_number() {
+        assert_eq!(
+            syntax_error("material red lambertian 1 x 0"),
+            (
+                1,
+                27,
+                "expected a green value, found `x`".to_string()
+            )
+        );
+        assert_eq!(
+            syntax_error("sphere 0 0 0 nan red"),
+            (
+                1,
+                14,
+                "expected a non-zero radius, found `nan`".to_string()
+            )
+        );
+    }
+
+    #[test]
+    fn missing_value_reported_at_end_of_line() {
+        assert_eq!(
+            syntax_error("sphere 0 0 # comment"),
+            (
+                1,
+                12,
+                "expected a z coordinate".to_string()
+            )
+        );
+    }
+
+    #[test]
+    fn unknown_material() {
+        assert_eq!(
+            syntax_error("sphere 0 0 0 1 red\nmaterial red lambertian 1 0 0"),
+            (
+                1,
+                16,
+                "unknown material `red`".to_string()
+            )
+        );
+    }
+
+    #[test]
+    fn duplicates() {
+        assert_eq!(
+            syntax_error(
+                "material red lambertian 1 0 0\nmaterial red light 1 0 0"
+            ),
+            (
+                2,
+                10,
+                "material `red` is defined twice".to_string()
+            )
+        );
+        assert_eq!(
+            syntax_error("render width 100 width 200"),
+            (
+                1,
+                18,
+                "render setting `width` is given twice".to_string()
+            )
+        );
+        assert_eq!(
+            syntax_error("render width 100\nrender samples 4 width 200"),
+            (
+                2,
+                18,
+                "render setting `width` is given twice".to_string()
+            )
+        );
+        assert_eq!(
+            syntax_error("camera fov 20\ncamera fov 30"),
+            (
+                2,
+                1,
+                "camera is defined twice".to_string()
+            )
+        );
+    }
+
+    #[test]
+    fn trailing_tokens() {
+        assert_eq!(
+            syntax_error("material glass dielectric 1.5 1.2"),
+            (
+                1,
+                31,
+                "unexpected `1.2`".to_string()
+            )
+        );
+    }
+
+    #[test]
+    fn invalid_settings() {
+        assert_eq!(
+            syntax_error("render width 0"),
+            (
+                1,
+                14,
+                "expected a positive width, found `0`".to_string()
+            )
+        );
+        assert_eq!(
+            syntax_error("camera from 0 1 0 at 0 1 0"),
+            (
+                1,
+                1,
+                "the camera looks at its own position".to_string()
+            )
+        );
+        assert_eq!(
+            syntax_error("\n  camera up 0 0 1"),
+            (
+                2,
+                3,
+                "`up` is parallel to the view direction".to_string()
+            )
+        );
+        assert_eq!(
+            syntax_error("camera zoom 2"),
+            (
+                1,
+                8,
+                "unknown camera setting `zoom`".to_string()
+            )
+        );
+        assert_eq!(
+            syntax_error("camera fov 0 aperture -3 focus -1"),
+            (
+                1,
+                12,
+                "expected a field of view between 0 and 180, found `0`"
+                    .to_string()
+            )
+        );
+        assert_eq!(
+            syntax_error("camera fov 180"),
+            (
+                1,
+                12,
+                "expected a field of view between 0 and 180, found `180`"
+                    .to_string()
+            )
+        );
+        assert_eq!(
+            syntax_error("camera aperture -3 focus -1"),
+            (
+                1,
+                17,
+                "expected a non-negative aperture, found `-3`".to_string()
+            )
+        );
+        assert_eq!(
+            syntax_error("camera focus -1"),
+            (
+                1,
+                14,
+                "expected a positive focus distance, found `-1`".to_string()
+            )
+        );
+        assert_eq!(
+            syntax_error("material g dielectric 0"),
+            (
+                1,
+                23,
+                "expected a positive index of refraction, found `0`"
+                    .to_string()
+            )
+        );
+        assert_eq!(
+            syntax_error("material g dielectric 1.5\nsphere 0 0 -1 0 g"),
+            (
+                2,
+                15,
+                "expected a non-zero radius, found `0`".to_string()
+            )
+        );
+        assert_eq!(
+            syntax_error("material x plastic 1 1 1"),
+            (
+                1,
+                12,
+                "unknown material type `plastic`".to_string()
+            )
+        );
+    }
+
+    #[test]
+    fn columns_count_characters() {
+        assert_eq!(
+            syntax_error("# ünïcödé\n\tmaterial é metal 1 1 ?"),
+            (
+                2,
+                23,
+                "expected a blue value, found `?`".to_string()
+            )
+        );
+    }
+
+    #[test]
+    fn invalid_utf8() {
+        assert!(matches!(
+            Scene::read(&[0xFF, 0xFE][..]),
+            Err(SceneError::InvalidText)
+        ));
+    }
+}
@@ -0,0 +1,824 @@
+//! JSON scene descriptions.
+//!
+//! A scene is an object with optional `settings`, `background`, `camera`
+//! and `objects` members, missing members take the same defaults as the
+//! text format. Unknown members are ignored so other tools can attach
+//! their own data.
+//!
+//! ```text
+//! {
+//!   "settings": {"width": 400, "height": 225, "samples_per_pixel": 100},
+//!   "background": "sky",
+//!   "camera": {"look_from": [-2, 2, 1], "look_at": [0, 0, -1],
+//!              "up": [0, 1, 0], "vertical_fov": 20, "aperture": 0.1},
+//!   "objects": [
+//!     {"type": "sphere", "position": [0, 0, -1], "radius": 0.5,
+//!      "material": {"type": "metal", "albedo": [0.8, 0.6, 0.2], "fuzz": 0}}
+//!   ]
+//! }
+//! ```
+//!
+//! Materials are written inline for every object, so objects sharing a
+//! material no longer share it after a round trip. Seeds above 2^53 don't
+//! survive as JSON numbers and are written as decimal strings instead.
+
+use std::any::Any;
+use std::io::Read;
+use std::sync::Arc;
+
+use super::bvh::BvhNode;
+use super::camera::CameraSettings;
+use super::hittable::Hittable;
+use super::json::Json;
+use super::material::Dielectric;
+use super::material::DiffuseLight;
+use super::material::Lambertian;
+use super::material::Material;
+use super::material::Metal;
+use super::material::Normal;
+use super::mesh::Mesh;
+use super::render::Background;
+use super::render::RenderSettings;
+use super::scene::Scene;
+use super::scene_file::read_text;
+use super::scene_file::SceneError;
+use super::sphere::Sphere;
+use super::triangle::Triangle;
+use super::world::World;
+use crate::math::vector3::Vector3;
+
+impl Scene {
+    /// Reads an entire JSON scene from `reader`.
+    pub fn read_json<R: Read>(reader: R) -> Result<Self, SceneError> {
+        Self::parse_json(&read_text(reader)?)
+    }
+
+    pub fn parse_json(source: &str) -> Result<Self, SceneError> {
+        Self::from_json(&Json::parse(source)?).map_err(SceneError::Invalid)
+    }
+
+    /// Builds a scene from a parsed document, errors name the offending
+    /// member.
+    pub fn from_json(json: &Json) -> Result<Self, String> {
+        if !matches!(json, Json::Object(_)) {
+            return Err("a scene must be an object".to_string());
+        }
+
+        let mut scene = Scene::default();
+
+        if let Some(settings) = json.get("settings") {
+            scene.settings =
+                settings_from_json(settings).map_err(context("settings"))?;
+        }
+        if let Some(background) = json.get("background") {
+            scene.background = background_from_json(background)
+                .map_err(context("background"))?;
+        }
+        if let Some(camera) = json.get("camera") {
+            scene.camera =
+                camera_from_json(camera).map_err(context("camera"))?;
+        }
+        if let Some(objects) = json.get("objects") {
+            let objects = objects
+                .as_array()
+                .ok_or_else(|| "`objects` must be an array".to_string())?;
+
+            for (index, object) in objects.iter().enumerate() {
+                let object = object_from_json(object).map_err(context(
+                    &format!("objects[{}]", index),
+                ))?;
+                scene.world.add(object);
+            }
+        }
+
+        Ok(scene)
+    }
+
+    /// Describes the whole scene, objects inside a BVH or mesh are written
+    /// as a flat list.
+    ///
+    /// Fails if the scene holds an object or material type the format
+    /// doesn't know.
+    pub fn to_json(&self) -> Result<Json, String> {
+        let mut objects = Vec::new();
+        objects_to_json(&self.world, &mut objects)
+            .map_err(context("objects"))?;
+
+        Ok(Json::object([
+            (
+                "settings",
+                settings_to_json(&self.settings),
+            ),
+            (
+                "background",
+                background_to_json(self.background),
+            ),
+            (
+                "camera",
+                camera_to_json(&self.camera),
+            ),
+            ("objects", Json::Array(objects)),
+        ]))
+    }
+}
+
+/// Prefixes errors with the member they happened in.
+fn context(name: impl std::fmt::Display) -> impl FnOnce(String) -> String {
+    move |error| format!("{}: {}", name, error)
+}
+
+/// Reads a single object. This and `objects_to_json` are the parts that
+/// grow with new primitives.
+fn object_from_json(json: &Json) -> Result<Box<dyn Hittable>, String> {
+    match json.str_field("type")? {
+        "sphere" => Ok(Box::new(sphere_from_json(json)?)),
+        "triangle" => Ok(Box::new(triangle_from_json(json)?)),
+        kind => Err(format!(
+            "unknown object type `{}`",
+            kind
+        )),
+    }
+}
+
+/// Appends a description of `object` to `objects`, containers append every
+/// object they hold instead.
+fn objects_to_json(
+    object: &dyn Hittable,
+    objects: &mut Vec<Json>,
+) -> Result<(), String> {
+    let object: &dyn Any = object;
+
+    if let Some(sphere) = object.downcast_ref::<Sphere>() {
+        objects.push(sphere_to_json(sphere)?);
+    } else if let Some(triangle) = object.downcast_ref::<Triangle>() {
+        objects.push(triangle_to_json(triangle)?);
+    } else if let Some(mesh) = object.downcast_ref::<Mesh>() {
+        for triangle in mesh.triangles() {
+            objects.push(triangle_to_json(triangle)?);
+        }
+    } else if let Some(world) = object.downcast_ref::<World>() {
+        for object in world.objects() {
+            objects_to_json(&**object, objects)?;
+        }
+    } else if let Some(bvh) = object.downcast_ref::<BvhNode>() {
+        // The hierarchy itself is rebuilt after loading.
+        for object in bvh.objects() {
+            objects_to_json(object, objects)?;
+        }
+    } else {
+        return Err(
+            "the scene holds an object JSON can't describe".to_string(),
+        );
+    }
+
+    Ok(())
+}
+
+fn sphere_to_json(sphere: &Sphere) -> Result<Json, String> {
+    Ok(Json::object([
+        ("type", Json::from("sphere")),
+        (
+            "position",
+            Json::from(sphere.position),
+        ),
+        ("radius", Json::from(sphere.radius)),
+        (
+            "material",
+            material_to_json(&*sphere.material)?,
+        ),
+    ]))
+}
+
+fn sphere_from_json(json: &Json) -> Result<Sphere, String> {
+    let material = material_from_json(json.field("material")?)
+        .map_err(context("material"))?;
+
+    Ok(Sphere::new(
+        json.vector3_field("position")?,
+        json.number_field("radius")?,
+        material,
+    ))
+}
+
+fn triangle_to_json(triangle: &Triangle) -> Result<Json, String> {
+    let triple = |vectors: [Vector3; 3]| {
+        Json::Array(vectors.iter().map(|vector| Json::from(*vector)).collect())
+    };
+
+    let mut members = vec![
+        ("type", Json::from("triangle")),
+        (
+            "vertices",
+            triple(triangle.vertices),
+        ),
+    ];
+    if let Some(normals) = triangle.normals {
+        members.push(("normals", triple(normals)));
+    }
+    if let Some(texcoords) = triangle.texcoords {
+        let pairs = texcoords
+            .iter()
+            .map(|(u, v)| {
+                Json::Array(vec![
+                    Json::from(*u),
+                    Json::from(*v),
+                ])
+            })
+            .collect();
+        members.push(("texcoords", Json::Array(pairs)));
+    }
+    members.push((
+        "cull_back_faces",
+        Json::Bool(triangle.cull_back_faces),
+    ));
+    members.push((
+        "material",
+        material_to_json(&*triangle.material)?,
+    ));
+
+    Ok(Json::object(members))
+}
+
+fn triangle_from_json(json: &Json) -> Result<Triangle, String> {
+    let material = material_from_json(json.field("material")?)
+        .map_err(context("material"))?;
+    let triple = |key: &str| {
+        let vectors = json
+            .field(key)?
+            .as_array()
+            .filter(|vectors| vectors.len() == 3)
+            .and_then(|vectors| {
+                vectors
+                    .iter()
+                    .map(Json::as_vector3)
+                    .collect::<Option<Vec<_>>>()
+            })
+            .ok_or_else(|| {
+                format!(
+                    "`{}` must be an array of three vectors",
+                    key
+                )
+            })?;
+        Ok::<_, String>([vectors[0], vectors[1], vectors[2]])
+    };
+
+    let [a, b, c] = triple("vertices")?;
+    let mut triangle = Triangle::new(a, b, c, material);
+    if json.get("normals").is_some() {
+        triangle.normals = Some(triple("normals")?);
+    }
+    if let Some(texcoords) = json.get("texcoords") {
+        let pair = |json: &Json| match json.as_array()? {
+            [u, v] => Some((u.as_f64()?, v.as_f64()?)),
+            _ => None,
+        };
+        let pairs = texcoords
+            .as_array()
+            .filter(|pairs| pairs.len() == 3)
+            .and_then(|pairs| {
+                pairs.iter().map(pair).collect::<Option<Vec<_>>>()
+            })
+            .ok_or_else(|| {
+                "`texcoords` must be an array of three pairs".to_string()
+            })?;
+        triangle.texcoords = Some([pairs[0], pairs[1], pairs[2]]);
+    }
+    triangle.cull_back_faces = match json.get("cull_back_faces") {
+        None => false,
+        Some(Json::Bool(cull)) => *cull,
+        Some(_) => {
+            return Err("`cull_back_faces` must be a boolean".to_string())
+        }
+    };
+
+    Ok(triangle)
+}
+
+/// Describes a material as an object with a `type` member.
+fn material_to_json(material: &dyn Material) -> Result<Json, String> {
+    let material: &dyn Any = material;
+
+    Ok(if material.is::<Normal>() {
+        Json::object([("type", Json::from("normal"))])
+    } else if let Some(lambertian) = material.downcast_ref::<Lambertian>() {
+        Json::object([
+            ("type", Json::from("lambertian")),
+            (
+                "albedo",
+                Json::from(lambertian.albedo),
+            ),
+        ])
+    } else if let Some(metal) = material.downcast_ref::<Metal>() {
+        Json::object([
+            ("type", Json::from("metal")),
+            ("albedo", Json::from(metal.albedo)),
+            ("fuzz", Json::from(metal.fuzz)),
+        ])
+    } else if let Some(dielectric) = material.downcast_ref::<Dielectric>() {
+        Json::object([
+            ("type", Json::from("dielectric")),
+            ("ior", Json::from(dielectric.ior)),
+        ])
+    } else if let Some(light) = material.downcast_ref::<DiffuseLight>() {
+        Json::object([
+            ("type", Json::from("light")),
+            ("emit", Json::from(light.emit)),
+        ])
+    } else {
+        return Err(
+            "the scene holds a material JSON can't describe".to_string(),
+        );
+    })
+}
+
+fn material_from_json(json: &Json) -> Result<Arc<dyn Material>, String> {
+    Ok(match json.str_field("type")? {
+        "normal" => Arc::new(Normal),
+        "lambertian" => Arc::new(Lambertian::new(
+            json.vector3_field("albedo")?,
+        )),
+        "metal" => Arc::new(Metal::new(
+            json.vector3_field("albedo")?,
+            json.number_field("fuzz")?,
+        )),
+        "dielectric" => Arc::new(Dielectric::new(
+            json.number_field("ior")?,
+        )),
+        "light" => Arc::new(DiffuseLight::new(
+            json.vector3_field("emit")?,
+        )),
+        kind => {
+            return Err(format!(
+                "unknown material type `{}`",
+                kind
+            ))
+        }
+    })
+}
+
+fn settings_to_json(settings: &RenderSettings) -> Json {
+    let members = [
+        ("width", settings.width),
+        ("height", settings.height),
+        (
+            "samples_per_pixel",
+            settings.samples_per_pixel.map(u64::from),
+        ),
+        (
+            "max_depth",
+            settings.max_depth.map(u64::from),
+        ),
+    ];
+
+    let mut members: Vec<_> = members
+        .into_iter()
+        .filter_map(|(key, value)| Some((key, Json::Number(value? as f64))))
+        .collect();
+    if let Some(seed) = settings.seed {
+        let seed = if seed <= MAX_EXACT_INTEGER {
+            Json::Number(seed as f64)
+        } else {
+            Json::String(seed.to_string())
+        };
+        members.push(("seed", seed));
+    }
+
+    Json::object(members)
+}
+
+/// Every integer up to 2^53 is exact as a JSON number.
+const MAX_EXACT_INTEGER: u64 = 1 << 53;
+
+fn seed_from_json(json: &Json) -> Result<u64, String> {
+    let seed = match json {
+        Json::String(seed) => seed.parse().ok(),
+        _ => match json.as_u64() {
+            Some(seed) if seed > MAX_EXACT_INTEGER => {
+                return Err(
+                    "`seed` above 2^53 must be written as a string"
+                        .to_string(),
+                )
+            }
+            seed => seed,
+        },
+    };
+
+    seed.ok_or_else(|| "`seed` must be an integer".to_string())
+}
+
+fn settings_from_json(json: &Json) -> Result<RenderSettings, String> {
+    let integer = |key: &str| match json.get(key) {
+        None => Ok(None),
+        Some(value) => value
+            .as_u64()
+            .filter(|value| *value > 0)
+            .map(Some)
+            .ok_or_else(|| {
+                format!(
+                    "`{}` must be a positive integer",
+                    key
+                )
+            }),
+    };
+    let small = |key: &str| -> Result<Option<u32>, String> {
+        integer(key)?
+            .map(|value| {
+                u32::try_from(value)
+                    .map_err(|_| format!("`{}` is too large", key))
+            })
+            .transpose()
+    };
+
+    Ok(RenderSettings {
+        width: integer("width")?,
+        height: integer("height")?,
+        samples_per_pixel: small("samples_per_pixel")?,
+        max_depth: small("max_depth")?,
+        seed: json.get("seed").map(seed_from_json).transpose()?,
+    })
+}
+
+fn background_to_json(background: Background) -> Json {
+    match background {
+        Background::Sky => Json::from("sky"),
+        Background::Solid(color) => Json::from(color),
+    }
+}
+
+fn background_from_json(json: &Json) -> Result<Background, String> {
+    match json.as_str() {
+        Some("sky") => Ok(Background::Sky),
+        _ => json.as_vector3().map(Background::Solid).ok_or_else(|| {
+            "expected `sky` or an array of three numbers".to_string()
+        }),
+    }
+}
+
+fn camera_to_json(camera: &CameraSettings) -> Json {
+    let mut members = vec![
+        (
+            "look_from",
+            Json::from(camera.look_from),
+        ),
+        (
+            "look_at",
+            Json::from(camera.look_at),
+        ),
+        ("up", Json::from(camera.up)),
+        (
+            "vertical_fov",
+            Json::from(camera.vertical_fov),
+        ),
+        (
+            "aperture",
+            Json::from(camera.aperture),
+        ),
+    ];
+    if let Some(focus_distance) = camera.focus_distance {
+        members.push((
+            "focus_distance",
+            Json::from(focus_distance),
+        ));
+    }
+
+    Json::object(members)
+}
+
+fn camera_from_json(json: &Json) -> Result<CameraSettings, String> {
+    let mut camera = CameraSettings::default();
+
+    if json.get("look_from").is_some() {
+        camera.look_from = json.vector3_field("look_from")?;
+    }
+    if json.get("look_at").is_some() {
+        camera.look_at = json.vector3_field("look_at")?;
+    }
+    if json.get("up").is_some() {
+        camera.up = json.vector3_field("up")?;
+    }
+    if json.get("vertical_fov").is_some() {
+        camera.vertical_fov = json.number_field("vertical_fov")?;
+    }
+    if json.get("aperture").is_some() {
+        camera.aperture = json.number_field("aperture")?;
+    }
+    if json.get("focus_distance").is_some() {
+        camera.focus_distance = Some(json.number_field("focus_distance")?);
+    }
+
+    camera.validate()?;
+    Ok(camera)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::math::aabb::Aabb;
+    use crate::math::random::Rng;
+    use crate::math::ray::Ray;
+    use crate::utils::bvh::SplitMethod;
+    use crate::utils::hittable::Hit;
+    use crate::utils::scene::BUILTIN_SCENES;
+    use std::ops::RangeInclusive;
+
+    fn invalid(source: &str) -> String {
+        match Scene::parse_json(source) {
+            Err(SceneError::Invalid(message)) => message,
+            Err(error) => panic!("unexpected error {}", error),
+            Ok(_) => panic!("expected an error"),
+        }
+    }
+
+    #[test]
+    fn builtin_scenes_round_trip() {
+        for name in BUILTIN_SCENES {
+            let mut scene = Scene::builtin(name).unwrap();
+            scene.settings.width = Some(320);
+            scene.settings.seed = Some(0);
+
+            let json = scene.to_json().unwrap();
+            let loaded = Scene::parse_json(&json.to_string()).unwrap();
+            assert_eq!(loaded.to_json().unwrap(), json);
+            assert_eq!(loaded.camera, scene.camera);
+            assert_eq!(loaded.settings, scene.settings);
+        }
+    }
+
+    #[test]
+    fn bvh_is_flattened() {
+        let mut scene = Scene::builtin("random").unwrap();
+        let before = scene.to_json().unwrap();
+
+        scene.world.build_bvh(SplitMethod::Sah { bins: 16 });
+        let after = scene.to_json().unwrap();
+
+        let count = |json: &Json| {
+            json.get("objects").and_then(Json::as_array).unwrap().len()
+        };
+        assert_eq!(count(&after), count(&before));
+    }
+
+    #[test]
+    fn triangle_round_trip() {
+        let mut triangle = Triangle::new(
+            Vector3::new(-1.0, -1.0, -1.0),
+            Vector3::new(1.0, -1.0, -1.0),
+            Vector3::new(0.0, 1.0, -1.0),
+            Arc::new(Normal),
+        );
+        triangle.normals = Some([Vector3::new(0.0, 0.0, 1.0); 3]);
+        triangle.texcoords = Some([(0.0, 0.0), (1.0, 0.0), (0.5, 1.0)]);
+        triangle.cull_back_faces = true;
+
+        let loaded =
+            triangle_from_json(&triangle_to_json(&triangle).unwrap()).unwrap();
+        assert_eq!(loaded.vertices, triangle.vertices);
+        assert_eq!(loaded.normals, triangle.normals);
+        assert_eq!(
+            loaded.texcoords,
+            triangle.texcoords
+        );
+        assert!(loaded.cull_back_faces);
+
+        let json = Json::parse(
+            r#"{"vertices": [[0, 0, 0], [1, 0, 0]],
+                "material": {"type": "normal"}}"#,
+        )
+        .unwrap();
+        assert_eq!(
+            triangle_from_json(&json).err(),
+            Some("`vertices` must be an array of three vectors".to_string())
+        );
+    }
+
+    #[test]
+    fn unknown_types_are_not_exported() {
+        struct Nothing;
+
+        impl Hittable for Nothing {
+            fn hit(
+                &self,
+                _ray: &Ray,
+                _valid_range: RangeInclusive<f64>,
+            ) -> Option<Hit<'_>> {
+                None
+            }
+
+            fn bounding_box(&self) -> Option<Aabb> {
+                None
+            }
+        }
+
+        let mut scene = Scene::default();
+        scene.world.add(Box::new(Nothing));
+        assert_eq!(
+            scene.to_json().err(),
+            Some(
+                "objects: the scene holds an object JSON can't describe"
+                    .to_string()
+            )
+        );
+
+        struct Black;
+
+        impl Material for Black {
+            fn scatter(
+                &self,
+                _ray: &Ray,
+                _hit: &Hit,
+                _rng: &mut Rng,
+            ) -> Option<(Vector3, Ray)> {
+                None
+            }
+        }
+
+        scene.world.clear();
+        scene.world.add(Box::new(Sphere::new(
+            Vector3::new(0.0, 0.0, -1.0),
+            0.5,
+            Arc::new(Black),
+        )));
+        assert_eq!(
+            scene.to_json().err(),
+            Some(
+                "objects: the scene holds a material JSON can't describe"
+                    .to_string()
+            )
+        );
+    }
+
+    #[test]
+    fn defaults_for_missing_members() {
+        let scene = Scene::parse_json("{}").unwrap();
+
+        assert_eq!(
+            scene.settings,
+            RenderSettings::default()
+        );
+        assert_eq!(
+            scene.camera,
+            CameraSettings::default()
+        );
+        assert!(matches!(
+            scene.background,
+            Background::Sky
+        ));
+        assert_eq!(scene.world.bounding_box(), None);
+    }
+
+    #[test]
+    fn large_seeds_round_trip() {
+        for seed in [MAX_EXACT_INTEGER, MAX_EXACT_INTEGER + 1, u64::MAX] {
+            let scene = Scene {
+                settings: RenderSettings {
+                    seed: Some(seed),
+                    ..Default::default()
+                },
+                ..Default::default()
+            };
+
+            let json = scene.to_json().unwrap().to_string();
+            let loaded = Scene::parse_json(&json).unwrap();
+            assert_eq!(loaded.settings.seed, Some(seed));
+        }
+
+        assert!(Scene::parse_json(
+            r#"{"settings": {"seed": 9007199254740992}}"#
+        )
+        .is_ok());
+        assert_eq!(
+            invalid(r#"{"settings": {"seed": 9007199254740994}}"#),
+            "settings: `seed` above 2^53 must be written as a string"
+        );
+        assert_eq!(
+            invalid(r#"{"settings": {"seed": "18446744073709551616"}}"#),
+            "settings: `seed` must be an integer"
+        );
+    }
+
+    #[test]
+    fn parse_scene() {
+        let scene = Scene::parse_json(
+            r#"{
+                "settings": {"width": 64, "seed": 0},
+                "background": [0, 0, 0],
+                "camera": {"look_from": [0, 0, 3], "look_at": [0, 0, 0]},
+                "objects": [
+                    {"type": "sphere", "position": [0, 0, 0], "radius": 1,
+                     "material": {"type": "light", "emit": [4, 4, 4]},
+                     "name": "ignored"}
+                ]
+            }"#,
+        )
+        .unwrap();
+
+        assert_eq!(scene.settings.width, Some(64));
+        assert_eq!(scene.settings.seed, Some(0));
+        assert_eq!(
+            scene.camera.look_from,
+            Vector3::new(0.0, 0.0, 3.0)
+        );
+        assert_eq!(scene.camera.vertical_fov, 90.0);
+        assert!(matches!(
+            scene.background,
+            Background::Solid(color) if color == Vector3::new(0.0, 0.0, 0.0)
+        ));
+
+        let ray =
+            scene
+                .camera
+                .build(1.0)
+                .get_ray(0.5, 0.5, &mut Default::default());
+        let hit = scene.world.hit(&ray, 0.001..=f64::MAX).unwrap();
+        assert_eq!(
+            hit.material.emitted(&hit),
+            Vector3::new(4.0, 4.0, 4.0)
+        );
+    }
+
+    #[test]
+    fn syntax_errors_have_positions() {
+        assert!(matches!(
+            Scene::parse_json("{\n  \"objects\": [}"),
+            Err(SceneError::Syntax {
+                line: 2,
+                column: 15,
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn invalid_scenes() {
+        assert_eq!(
+            invalid("[]"),
+            "a scene must be an object"
+        );
+        assert_eq!(
+            invalid(r#"{"objects": {}}"#),
+            "`objects` must be an array"
+        );
+        assert_eq!(
+            invalid(r#"{"settings": {"width": 0}}"#),
+            "settings: `width` must be a positive integer"
+        );
+        assert_eq!(
+            invalid(r#"{"settings": {"seed": -1}}"#),
+            "settings: `seed` must be an integer"
+        );
+        assert_eq!(
+            invalid(r#"{"settings": {"samples_per_pixel": 5000000000}}"#),
+            "settings: `samples_per_pixel` is too large"
+        );
+        assert_eq!(
+            invalid(r#"{"background": "night"}"#),
+            "background: expected `sky` or an array of three numbers"
+        );
+        assert_eq!(
+            invalid(r#"{"camera": {"up": [0, 1]}}"#),
+            "camera: `up` must be an array of three numbers"
+        );
+        assert_eq!(
+            invalid(r#"{"camera": {"look_at": [0, 0, 0]}}"#),
+            "camera: the camera looks at its own position"
+        );
+        assert_eq!(
+            invalid(r#"{"camera": {"look_from": [1e999, 0, 0]}}"#),
+            "camera: `look_from` must be finite"
+        );
+        assert_eq!(
+            invalid(
+                r#"{"objects": [{"type": "sphere", "position": [0, 0, 0],
+                "radius": 1e999, "material": {"type": "normal"}}]}"#
+            ),
+            "objects[0]: `radius` must be finite"
+        );
+        assert_eq!(
+            invalid(r#"{"camera": {"vertical_fov": 0}}"#),
+            "camera: the field of view must be between 0 and 180 degrees"
+        );
+        assert_eq!(
+            invalid(r#"{"camera": {"aperture": -1}}"#),
+            "camera: the aperture must not be negative"
+        );
+        assert_eq!(
+            invalid(r#"{"camera": {"up": [0, 0, -1]}}"#),
+            "camera: `up` is parallel to the view direction"
+        );
+        assert_eq!(
+            invalid(r#"{"objects": [{"type": "cube"}]}"#),
+            "objects[0]: unknown object type `cube`"
+        );
+        assert_eq!(
+            invalid(
+                r#"{"objects": [{"type": "sphere", "position": [0, 0, 0],
+                "radius": 1, "material": {"type": "metal", "albedo": [1, 1, 1]}}]}"#
+            ),
+            "objects[0]: material: missing `fuzz`"
+        );
+        assert_eq!(
+            invalid(r#"{"objects": [{"type": "sphere"}]}"#),
+            "objects[0]: missing `material`"
+        );
+    }
+}
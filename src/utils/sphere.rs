@@ -1,23 +1,39 @@
 use super::hittable::*;
+use super::material::Material;
+use crate::math::aabb::Aabb;
 use crate::math::ray::Ray;
 use crate::math::vector3::Vector3;
 use std::ops::RangeInclusive;
+use std::sync::Arc;
 
 /// Holds information for a sphere.
 pub struct Sphere {
     pub position: Vector3,
     pub radius: f64,
+    pub material: Arc<dyn Material>,
 }
 
 impl Sphere {
-    pub fn new(position: Vector3, radius: f64) -> Self {
-        Self { position, radius }
+    pub fn new(
+        position: Vector3,
+        radius: f64,
+        material: Arc<dyn Material>,
+    ) -> Self {
+        Self {
+            position,
+            radius,
+            material,
+        }
     }
 }
 
 impl Hittable for Sphere {
     /// Performs a ray-sphere intersection and returns the `Hit` object.
-    fn hit(&self, ray: &Ray, valid_range: RangeInclusive<f64>) -> Option<Hit> {
+    fn hit(
+        &self,
+        ray: &Ray,
+        valid_range: RangeInclusive<f64>,
+    ) -> Option<Hit<'_>> {
         let oc = ray.origin - self.position;
         let a = ray.direction.dot(ray.direction);
         let half_b = oc.dot(ray.direction);
@@ -42,10 +58,24 @@ impl Hittable for Sphere {
             position: pos,
             normal: outward_normal,
             t: root,
+            front_face: true,
+            barycentric: None,
+            material: &*self.material,
         };
 
         hit.set_face_normal(ray, outward_normal);
 
         Some(hit)
     }
+
+    fn bounding_box(&self) -> Option<Aabb> {
+        // Hollow spheres have a negative radius.
+        let radius = self.radius.abs();
+        let extent = Vector3::new(radius, radius, radius);
+
+        Some(Aabb::new(
+            self.position - extent,
+            self.position + extent,
+        ))
+    }
 }
@@ -0,0 +1,193 @@
+use super::image::HdrImage;
+use crate::math::vector3::Vector3;
+
+/// Curve used to compress radiance above 1 into the displayable range.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Operator {
+    /// Leaves radiance unchanged, anything above 1 is clipped later.
+    Clip,
+    /// Maps luminance `L` to `L / (1 + L)`.
+    Reinhard,
+    /// Reinhard with a white point, luminance at or above `white` maps
+    /// to 1.
+    ExtendedReinhard { white: f64 },
+    /// Krzysztof Narkowicz's fit of the ACES filmic curve, applied to every
+    /// channel.
+    Aces,
+}
+
+/// Maps linear radiance from a float framebuffer into `[0, 1]` before it is
+/// encoded and quantized.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ToneMapper {
+    pub operator: Operator,
+    /// Exposure adjustment in stops, every stop doubles the radiance.
+    pub exposure: f64,
+}
+
+impl Default for ToneMapper {
+    fn default() -> Self {
+        Self::new(Operator::Clip)
+    }
+}
+
+impl ToneMapper {
+    /// Creates a tone mapper with no exposure adjustment.
+    pub fn new(operator: Operator) -> Self {
+        Self {
+            operator,
+            exposure: 0.0,
+        }
+    }
+
+    /// Tone maps a single radiance value.
+    pub fn map(&self, radiance: Vector3) -> Vector3 {
+        let radiance = radiance * 2f64.powf(self.exposure);
+
+        match self.operator {
+            Operator::Clip => radiance,
+            Operator::Reinhard => scale_luminance(radiance, |luminance| {
+                luminance / (1.0 + luminance)
+            }),
+            Operator::ExtendedReinhard { white } => {
+                scale_luminance(radiance, |luminance| {
+                    luminance * (1.0 + luminance / (white * white))
+                        / (1.0 + luminance)
+                })
+            }
+            Operator::Aces => Vector3::new(
+                aces(radiance.x),
+                aces(radiance.y),
+                aces(radiance.z),
+            ),
+        }
+    }
+
+    /// Returns a tone mapped copy of `image`.
+    pub fn apply(&self, image: &HdrImage) -> HdrImage {
+        let mut mapped = image.clone();
+        for pixel in mapped.pixels.iter_mut() {
+            *pixel = self.map(*pixel);
+        }
+        mapped
+    }
+}
+
+/// Relative luminance of linear Rec. 709 radiance.
+pub fn luminance(radiance: Vector3) -> f64 {
+    0.2126 * radiance.x + 0.7152 * radiance.y + 0.0722 * radiance.z
+}
+
+/// Scales `radiance` so its luminance becomes `curve(luminance)`, keeping
+/// the ratio between channels.
+fn scale_luminance(radiance: Vector3, curve: impl Fn(f64) -> f64) -> Vector3 {
+    let luminance = luminance(radiance);
+    if luminance <= 0.0 {
+        return Vector3::new(0.0, 0.0, 0.0);
+    }
+
+    radiance * (curve(luminance) / luminance)
+}
+
+fn aces(value: f64) -> f64 {
+    let value = value.max(0.0);
+    let mapped =
+        value * (2.51 * value + 0.03) / (value * (2.43 * value + 0.59) + 0.14);
+    mapped.clamp(0.0, 1.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn gray(value: f64) -> Vector3 {
+        Vector3::new(value, value, value)
+    }
+
+    fn close(a: Vector3, b: Vector3) -> bool {
+        (a - b).near_zero()
+    }
+
+    #[test]
+    fn clip_is_identity() {
+        let radiance = Vector3::new(4.0, 0.5, 0.0);
+        assert_eq!(
+            ToneMapper::default().map(radiance),
+            radiance
+        );
+    }
+
+    #[test]
+    fn exposure_in_stops() {
+        let mut mapper = ToneMapper::new(Operator::Clip);
+        mapper.exposure = 2.0;
+        assert_eq!(mapper.map(gray(0.25)), gray(1.0));
+
+        mapper.exposure = -1.0;
+        assert_eq!(mapper.map(gray(0.25)), gray(0.125));
+    }
+
+    #[test]
+    fn reinhard() {
+        let mapper = ToneMapper::new(Operator::Reinhard);
+        assert!(close(
+            mapper.map(gray(1.0)),
+            gray(0.5)
+        ));
+        assert!(close(
+            mapper.map(gray(3.0)),
+            gray(0.75)
+        ));
+        assert_eq!(mapper.map(gray(0.0)), gray(0.0));
+    }
+
+    #[test]
+    fn reinhard_keeps_hue() {
+        let mapped = ToneMapper::new(Operator::Reinhard)
+            .map(Vector3::new(4.0, 2.0, 1.0));
+        assert!((mapped.x / mapped.y - 2.0).abs() < 1e-12);
+        assert!((mapped.y / mapped.z - 2.0).abs() < 1e-12);
+    }
+
+    #[test]
+    fn extended_reinhard_white_point() {
+        let mapper =
+            ToneMapper::new(Operator::ExtendedReinhard { white: 4.0 });
+        assert!(close(
+            mapper.map(gray(4.0)),
+            gray(1.0)
+        ));
+        assert!(mapper.map(gray(1.0)).x > 0.5);
+    }
+
+    #[test]
+    fn aces_is_bounded_and_monotonic() {
+        let mapper = ToneMapper::new(Operator::Aces);
+        assert_eq!(mapper.map(gray(0.0)), gray(0.0));
+        assert_eq!(mapper.map(gray(1000.0)), gray(1.0));
+
+        let mut previous = 0.0;
+        for i in 1..100 {
+            let mapped = mapper.map(gray(i as f64 / 10.0)).x;
+            assert!(mapped >= previous);
+            previous = mapped;
+        }
+    }
+
+    #[test]
+    fn apply_maps_every_pixel() {
+        let mut image = HdrImage::new(2, 1);
+        image.set(0, 0, gray(1.0));
+        image.set(1, 0, gray(3.0));
+
+        let mapped = ToneMapper::new(Operator::Reinhard).apply(&image);
+        assert!(close(
+            *mapped.get(0, 0).unwrap(),
+            gray(0.5)
+        ));
+        assert!(close(
+            *mapped.get(1, 0).unwrap(),
+            gray(0.75)
+        ));
+    }
+}
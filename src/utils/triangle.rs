@@ -0,0 +1,292 @@
+use super::hittable::*;
+use super::material::Material;
+use crate::math::aabb::Aabb;
+use crate::math::ray::Ray;
+use crate::math::vector3::Vector3;
+use std::ops::RangeInclusive;
+use std::sync::Arc;
+
+/// Rays closer to parallel with the plane than this are treated as misses.
+const EPSILON: f64 = 1e-12;
+/// Half the thickness given to the bounding box of an axis aligned
+/// triangle, so the box is never flat.
+const PADDING: f64 = 1e-4;
+
+/// Holds information for a triangle.
+///
+/// The front face is the side the vertices appear counter clockwise from.
+pub struct Triangle {
+    pub vertices: [Vector3; 3],
+    /// Per vertex normals interpolated for smooth shading, the face normal
+    /// is used if `None`.
+    pub normals: Option<[Vector3; 3]>,
+    /// Per vertex texture coordinates, kept for texturing.
+    pub texcoords: Option<[(f64, f64); 3]>,
+    /// Ignores rays hitting the back face.
+    pub cull_back_faces: bool,
+    pub material: Arc<dyn Material>,
+}
+
+impl Triangle {
+    /// Creates a flat shaded, double sided triangle.
+    pub fn new(
+        a: Vector3,
+        b: Vector3,
+        c: Vector3,
+        material: Arc<dyn Material>,
+    ) -> Self {
+        Self {
+            vertices: [a, b, c],
+            normals: None,
+            texcoords: None,
+            cull_back_faces: false,
+            material,
+        }
+    }
+}
+
+impl Hittable for Triangle {
+    /// Möller–Trumbore intersection, solves for the distance and the
+    /// barycentric coordinates at once.
+    fn hit(
+        &self,
+        ray: &Ray,
+        valid_range: RangeInclusive<f64>,
+    ) -> Option<Hit<'_>> {
+        let [a, b, c] = self.vertices;
+        let mut edge1 = b - a;
+        let edge2 = c - a;
+
+        let mut direction = ray.direction;
+        let p = direction.cross(edge2);
+        let determinant = edge1.dot(p);
+
+        // A negative determinant means the ray sees the back face.
+        if determinant.abs() < EPSILON
+            || (self.cull_back_faces && determinant < 0.0)
+        {
+            return None;
+        }
+        let inverse = 1.0 / determinant;
+
+        let mut s = ray.origin - a;
+        let u = s.dot(p) * inverse;
+        if !(0.0..=1.0).contains(&u) {
+            return None;
+        }
+
+        let q = s.cross(edge1);
+        let v = ray.direction.dot(q) * inverse;
+        if v < 0.0 || u + v > 1.0 {
+            return None;
+        }
+
+        let t = edge2.dot(q) * inverse;
+        if !valid_range.contains(&t) {
+            return None;
+        }
+
+        let geometric = edge1.cross(edge2);
+        let front_face = ray.direction.dot(geometric) < 0.0;
+        let mut normal = match self.normals {
+            Some([na, nb, nc]) => (1.0 - u - v) * na + u * nb + v * nc,
+            None => geometric,
+        };
+        normal = normal.unit();
+
+        // Flip against the ray using the geometric normal, interpolated
+        // normals may point the other way near silhouettes.
+        Some(Hit {
+            position: ray.at(t),
+            normal: if front_face { normal } else { -normal },
+            t,
+            front_face,
+            barycentric: Some((u, v)),
+            material: &*self.material,
+        })
+    }
+
+    fn bounding_box(&self) -> Option<Aabb> {
+        let [a, b, c] = self.vertices;
+        let padding = Vector3::new(PADDING, PADDING, PADDING);
+
+        let bbox = Aabb::new(a, a)
+            .surrounding(&Aabb::new(b, b))
+            .surrounding(&Aabb::new(c, c));
+        Some(Aabb::new(
+            bbox.min - padding,
+            bbox.max + padding,
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::material::Normal;
+
+    /// Triangle in the z = -1 plane facing the origin.
+    fn triangle() -> Triangle {
+        Triangle::new(
+            Vector3::new(-1.0, -1.0, -1.0),
+            Vector3::new(1.0, -1.0, -1.0),
+            Vector3::new(0.0, 1.0, -1.0),
+            Arc::new(Normal),
+        )
+    }
+
+    fn ray_towards(x: f64, y: f64) -> Ray {
+        Ray::new(
+            Vector3::new(0.0, 0.0, 0.0),
+            Vector3::new(x, y, -1.0),
+        )
+    }
+
+    #[test]
+    fn hit_front_face() {
+        let triangle = triangle();
+        let hit = triangle
+            .hit(
+                &ray_towards(0.0, 0.0),
+                0.0..=f64::MAX,
+            )
+            .unwrap();
+
+        assert_eq!(hit.t, 1.0);
+        assert_eq!(
+            hit.position,
+            Vector3::new(0.0, 0.0, -1.0)
+        );
+        assert_eq!(
+            hit.normal,
+            Vector3::new(0.0, 0.0, 1.0)
+        );
+        assert!(hit.front_face);
+    }
+
+    #[test]
+    fn barycentric_coordinates() {
+        let triangle = triangle();
+        let [a, b, c] = triangle.vertices;
+
+        for (weights, target) in [
+            ((0.0, 0.0), a),
+            ((1.0, 0.0), b),
+            ((0.0, 1.0), c),
+            (
+                (0.25, 0.5),
+                0.25 * a + 0.25 * b + 0.5 * c,
+            ),
+        ] {
+            let hit = triangle
+                .hit(
+                    &Ray::new(Vector3::new(0.0, 0.0, 0.0), target),
+                    0.0..=f64::MAX,
+                )
+                .unwrap();
+            let (u, v) = hit.barycentric.unwrap();
+
+            assert!((u - weights.0).abs() < 1e-12);
+            assert!((v - weights.1).abs() < 1e-12);
+            assert!((hit.position - target).near_zero());
+        }
+    }
+
+    #[test]
+    fn misses() {
+        let triangle = triangle();
+
+        // Outside the edges, parallel to the plane, and behind the origin.
+        assert!(triangle
+            .hit(
+                &ray_towards(0.9, 0.9),
+                0.0..=f64::MAX
+            )
+            .is_none());
+        assert!(triangle
+            .hit(
+                &ray_towards(0.0, -1.5),
+                0.0..=f64::MAX
+            )
+            .is_none());
+        assert!(triangle
+            .hit(
+                &Ray::new(
+                    Vector3::new(0.0, 0.0, 0.0),
+                    Vector3::new(1.0, 0.0, 0.0)
+                ),
+                0.0..=f64::MAX
+            )
+            .is_none());
+        assert!(triangle
+            .hit(
+                &Ray::new(
+                    Vector3::new(0.0, 0.0, -2.0),
+                    Vector3::new(0.0, 0.0, -1.0)
+                ),
+                0.0..=f64::MAX
+            )
+            .is_none());
+        assert!(triangle.hit(&ray_towards(0.0, 0.0), 0.0..=0.5).is_none());
+    }
+
+    #[test]
+    fn back_face() {
+        let mut triangle = triangle();
+        let ray = Ray::new(
+            Vector3::new(0.0, 0.0, -2.0),
+            Vector3::new(0.0, 0.0, 1.0),
+        );
+
+        let hit = triangle.hit(&ray, 0.0..=f64::MAX).unwrap();
+        assert!(!hit.front_face);
+        assert_eq!(
+            hit.normal,
+            Vector3::new(0.0, 0.0, -1.0)
+        );
+
+        triangle.cull_back_faces = true;
+        assert!(triangle.hit(&ray, 0.0..=f64::MAX).is_none());
+        assert!(triangle
+            .hit(
+                &ray_towards(0.0, 0.0),
+                0.0..=f64::MAX
+            )
+            .is_some());
+    }
+
+    #[test]
+    fn interpolated_normals() {
+        let mut triangle = triangle();
+        triangle.normals = Some([
+            Vector3::new(1.0, 0.0, 0.0),
+            Vector3::new(0.0, 1.0, 0.0),
+            Vector3::new(0.0, 0.0, 1.0),
+        ]);
+        let [a, _, c] = triangle.vertices;
+
+        let target = 0.5 * a + 0.5 * c;
+        let hit = triangle
+            .hit(
+                &Ray::new(Vector3::new(0.0, 0.0, 0.0), target),
+                0.0..=f64::MAX,
+            )
+            .unwrap();
+
+        let expected = 0.5f64.sqrt();
+        assert!(
+            (hit.normal - Vector3::new(expected, 0.0, expected)).near_zero()
+        );
+    }
+
+    #[test]
+    fn flat_triangle_has_thick_box() {
+        let bbox = triangle().bounding_box().unwrap();
+
+        assert!(bbox.max.z > bbox.min.z);
+        assert!(bbox.hit(
+            &ray_towards(0.0, 0.0),
+            0.0..=f64::MAX
+        ));
+    }
+}
@@ -1,6 +1,10 @@
 use std::ops::RangeInclusive;
 
+use super::bvh::BvhNode;
+use super::bvh::BvhStats;
+use super::bvh::SplitMethod;
 use super::hittable::*;
+use crate::math::aabb::Aabb;
 use crate::math::ray::Ray;
 
 #[derive(Default)]
@@ -14,14 +18,43 @@ impl World {
         self.objects.push(object);
     }
 
+    pub fn objects(&self) -> &[Box<dyn Hittable>] {
+        &self.objects
+    }
+
     pub fn clear(&mut self) {
         self.objects.clear();
     }
+
+    /// Compiles every bounded object into a `BvhNode`, unbounded objects
+    /// are still tested linearly.
+    ///
+    /// Returns the statistics of the built hierarchy, if any.
+    pub fn build_bvh(&mut self, method: SplitMethod) -> Option<BvhStats> {
+        let (bounded, mut objects): (Vec<_>, Vec<_>) = self
+            .objects
+            .drain(..)
+            .partition(|object| object.bounding_box().is_some());
+
+        let mut stats = None;
+        if !bounded.is_empty() {
+            let bvh = BvhNode::with_split(bounded, method);
+            stats = Some(bvh.stats());
+            objects.push(Box::new(bvh));
+        }
+
+        self.objects = objects;
+        stats
+    }
 }
 
 impl Hittable for World {
     /// Loops through the world's objects and returns the closest `Hit`.
-    fn hit(&self, ray: &Ray, valid_range: RangeInclusive<f64>) -> Option<Hit> {
+    fn hit(
+        &self,
+        ray: &Ray,
+        valid_range: RangeInclusive<f64>,
+    ) -> Option<Hit<'_>> {
         let mut closest = *valid_range.end();
         let mut hit_anything = None;
 
@@ -35,4 +68,14 @@ impl Hittable for World {
 
         hit_anything
     }
+
+    /// Returns the box around every object, or `None` if the world is
+    /// empty or contains an unbounded object.
+    fn bounding_box(&self) -> Option<Aabb> {
+        self.objects
+            .iter()
+            .map(|object| object.bounding_box())
+            .reduce(|a, b| Some(a?.surrounding(&b?)))
+            .flatten()
+    }
 }
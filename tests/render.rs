@@ -1,12 +1,18 @@
-use math::ray::Ray;
+use indicatif::ProgressBar;
+use math::random::Rng;
 use math::vector3::Vector3;
 use ray_tracing::math;
 use ray_tracing::utils;
 use std::fs::read_to_string;
+use std::fs::File;
+use std::sync::Arc;
 use utils::camera::Camera;
+use utils::color::Transfer;
 use utils::hittable::*;
+use utils::material::Normal;
 use utils::ppm::RGBTriplet;
 use utils::ppm::PPM;
+use utils::render::Renderer;
 use utils::sphere::Sphere;
 use utils::world::World;
 
@@ -28,22 +34,13 @@ fn sky() {
         origin,
     );
 
-    let lower_left_corner = camera.position
-        - camera.horizontal / 2.0
-        - camera.vertical / 2.0
-        - Vector3::new(0.0, 0.0, camera.focal_length);
+    let mut rng = Rng::default();
 
     for j in (0..image_height).rev() {
         for i in 0..image_width {
             let u = i as f64 / (image_width - 1) as f64;
             let v = j as f64 / (image_height - 1) as f64;
-            let mut r = Ray::new(
-                camera.position,
-                lower_left_corner
-                    + u * camera.horizontal
-                    + v * camera.vertical
-                    - camera.position,
-            );
+            let mut r = camera.get_ray(u, v, &mut rng);
 
             let unit_direction = r.direction.unit();
             let t = 0.5 * (unit_direction.y + 1.0);
@@ -83,30 +80,22 @@ fn sphere() {
         origin,
     );
 
-    let lower_left_corner = camera.position
-        - camera.horizontal / 2.0
-        - camera.vertical / 2.0
-        - Vector3::new(0.0, 0.0, camera.focal_length);
+    let mut rng = Rng::default();
 
     for j in (0..image_height).rev() {
         for i in 0..image_width {
             let u = i as f64 / (image_width - 1) as f64;
             let v = j as f64 / (image_height - 1) as f64;
-            let mut r = Ray::new(
-                camera.position,
-                lower_left_corner
-                    + u * camera.horizontal
-                    + v * camera.vertical
-                    - camera.position,
-            );
+            let mut r = camera.get_ray(u, v, &mut rng);
 
             let mut world = World::default();
             world.add(Box::new(Sphere::new(
                 Vector3::new(0.0, 0.0, -1.0),
                 0.5,
+                Arc::new(Normal),
             )));
 
-            if let Some(_) = world.hit(&r, 0.0..=f64::MAX) {
+            if world.hit(&r, 0.0..=f64::MAX).is_some() {
                 ppm.set(
                     i,
                     image_height - j - 1,
@@ -152,27 +141,19 @@ fn normal_sphere() {
         origin,
     );
 
-    let lower_left_corner = camera.position
-        - camera.horizontal / 2.0
-        - camera.vertical / 2.0
-        - Vector3::new(0.0, 0.0, camera.focal_length);
+    let mut rng = Rng::default();
 
     for j in (0..image_height).rev() {
         for i in 0..image_width {
             let u = i as f64 / (image_width - 1) as f64;
             let v = j as f64 / (image_height - 1) as f64;
-            let mut r = Ray::new(
-                camera.position,
-                lower_left_corner
-                    + u * camera.horizontal
-                    + v * camera.vertical
-                    - camera.position,
-            );
+            let mut r = camera.get_ray(u, v, &mut rng);
 
             let mut world = World::default();
             world.add(Box::new(Sphere::new(
                 Vector3::new(0.0, 0.0, -1.0),
                 0.5,
+                Arc::new(Normal),
             )));
 
             if let Some(hit) = world.hit(&r, 0.0..=f64::MAX) {
@@ -204,3 +185,30 @@ fn normal_sphere() {
 
     assert_eq!(ppm.to_string(), cmp);
 }
+
+#[test]
+fn sky_renderer() {
+    let aspect_ratio = 16.0 / 9.0;
+    let image_width = 400;
+    let image_height = (image_width as f64 / aspect_ratio) as u64;
+
+    let cmp = PPM::read(File::open("./tests/sky.ppm").unwrap()).unwrap();
+
+    let camera = Camera::new(
+        2.0,
+        aspect_ratio * 2.0,
+        1.0,
+        Vector3::new(0.0, 0.0, 0.0),
+    );
+
+    let mut renderer = Renderer::new(image_width, image_height);
+    renderer.samples_per_pixel = 1;
+
+    let image = renderer.render(
+        &World::default(),
+        &camera,
+        &ProgressBar::hidden(),
+    );
+
+    assert!(image.to_ppm(Transfer::Linear) == cmp);
+}